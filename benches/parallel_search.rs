@@ -0,0 +1,32 @@
+// Playouts per second of tree parallel search as the thread count grows.
+// Run with `cargo bench --bench parallel_search [max threads]`
+use std::{thread, time::Duration};
+
+use four_monties::{
+    game::board::Board,
+    mcst::{budget::SearchBudget, SearchTree},
+};
+
+const SEARCH_TIME: Duration = Duration::from_secs(3);
+
+fn main() {
+    let cores = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    // Cargo passes its own flags through, so take the first numeric argument
+    let max_threads = std::env::args().find_map(|arg| arg.parse().ok()).unwrap_or(cores);
+    let mut threads = 1;
+    let mut baseline = None;
+
+    println!("{cores} cores available");
+    while threads <= max_threads {
+        let mut tree = SearchTree::new(Board::default(), 10);
+        let progress = tree.search_parallel(SearchBudget::Time(SEARCH_TIME), threads);
+
+        let rate = progress.playouts as f64 / progress.elapsed.as_secs_f64();
+        let speedup = rate / *baseline.get_or_insert(rate);
+        println!(
+            "{threads:>3} threads: {:>10.0} playouts/s ({speedup:.2}x), {} nodes",
+            rate, progress.nodes
+        );
+        threads *= 2;
+    }
+}
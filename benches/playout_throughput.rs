@@ -0,0 +1,36 @@
+// Random playouts per second from the empty board, the number the bitboard layout is tuned for.
+// Run with `cargo bench --bench playout_throughput [seconds]`
+use std::time::{Duration, Instant};
+
+use four_monties::{
+    game::board::Board,
+    mcst::playout::{self, PlayoutPolicy, Tactical, UniformRandom},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn measure(name: &str, policy: &dyn PlayoutPolicy<Board>, duration: Duration) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut moves = Vec::with_capacity(64);
+    let (mut playouts, mut plies) = (0u64, 0u64);
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        moves.clear();
+        playout::from(Board::default(), policy, &mut rng, &mut moves);
+        playouts += 1;
+        plies += moves.len() as u64;
+    }
+    let seconds = start.elapsed().as_secs_f64();
+    println!(
+        "{name:>8}: {:>10.0} playouts/s, {:>11.0} plies/s",
+        playouts as f64 / seconds,
+        plies as f64 / seconds
+    );
+}
+
+fn main() {
+    // Cargo passes its own flags through, so take the first numeric argument
+    let seconds = std::env::args().find_map(|arg| arg.parse().ok()).unwrap_or(3);
+    let duration = Duration::from_secs(seconds);
+    measure("uniform", &UniformRandom, duration);
+    measure("tactical", &Tactical, duration);
+}
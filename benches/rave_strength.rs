@@ -0,0 +1,44 @@
+// Score of RAVE against plain UCB1 Monty with the same playouts per move, swapping colours each game. Early
+// stopping is off so both sides always spend the whole budget.
+// Run with `cargo bench --bench rave_strength [games]`
+use four_monties::{
+    agent::{monty::Monty, Agent},
+    game::{board::Board, player::Player, result::GameResult},
+    mcst::{budget::SearchBudget, selection::Rave},
+    tournament::Tournament,
+};
+
+const PLAYOUTS: usize = 5_000;
+const SIMULATIONS: usize = 5;
+
+fn monty(rave: bool) -> Box<dyn Agent> {
+    let monty = Monty::with_budget(Board::default(), SearchBudget::Playouts(PLAYOUTS), SIMULATIONS).with_early_stop(false);
+    match rave {
+        true => Box::new(monty.with_selection_policy(Box::new(Rave::default()))),
+        false => Box::new(monty),
+    }
+}
+
+fn main() {
+    // Cargo passes its own flags through, so take the first numeric argument
+    let games: usize = std::env::args().find_map(|arg| arg.parse().ok()).unwrap_or(20);
+    let (mut wins, mut draws) = (0, 0);
+
+    for game in 0..games {
+        let rave_colour = if game % 2 == 0 { Player::Yellow } else { Player::Blue };
+        let (yellow, blue) = match rave_colour {
+            Player::Yellow => (monty(true), monty(false)),
+            _ => (monty(false), monty(true)),
+        };
+        let outcome = Tournament::new(yellow, blue).with_seed(game as u64).play();
+        match outcome.result {
+            GameResult::Draw => draws += 1,
+            GameResult::Win(winner) if winner == rave_colour => wins += 1,
+            _ => (),
+        }
+        println!("game {:>3}: rave as {rave_colour}, {}", game + 1, outcome.result);
+    }
+
+    let score = (wins as f64 + draws as f64 / 2.0) / games as f64;
+    println!("RAVE scored {wins} wins, {draws} draws from {games} games ({:.1}%) at {PLAYOUTS} playouts per move", score * 100.0);
+}
@@ -1,10 +1,32 @@
-use crate::game::board::Board;
-
-pub mod monty;
-pub mod randy;
-pub mod yu;
-
-pub trait Agent {
-    fn select_move(&mut self, board: Board) -> usize;
-    fn record_move(&mut self, index: usize, board: Board) -> Board;
-}
+use crate::{game::board::Board, mcst::error::SearchError};
+
+pub mod monties;
+pub mod monty;
+pub mod randy;
+pub mod solver;
+pub mod yu;
+
+// What an agent does with its turn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decision {
+    Move(usize),
+    // Take back the agent's own last move and the reply to it
+    TakeBack,
+}
+
+pub trait Agent {
+    // Errors when the agent can't come up with a move, which forfeits the game
+    fn select_move(&mut self, board: Board) -> Result<usize, SearchError>;
+    fn record_move(&mut self, index: usize, board: Board) -> Board;
+
+    // Called for each turn by the tournament, only agents that ask for take-backs need more than select_move
+    fn decide(&mut self, board: Board) -> Result<Decision, SearchError> {
+        self.select_move(board).map(Decision::Move)
+    }
+
+    // The game has been taken back to this position, agents that track the game must rebuild from it
+    fn taken_back(&mut self, _board: Board) {}
+
+    // Reseeds any randomness so a game can be replayed exactly, deterministic agents ignore it
+    fn seed(&mut self, _seed: u64) {}
+}
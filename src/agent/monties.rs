@@ -0,0 +1,131 @@
+use std::thread;
+
+use crate::{
+    game::board::Board,
+    mcst::{self, budget::SearchBudget, error::SearchError, record::Record, MoveStats, SearchTree},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::Agent;
+
+// Root parallel ensemble: independent search trees on their own threads, with root statistics merged to pick a move
+pub struct Monties {
+    search_trees: Vec<SearchTree<Board>>,
+    budget: SearchBudget,
+}
+
+impl Monties {
+    // Each tree stops early once its best move can't change within the budget
+    pub fn new(board: Board, trees: usize, budget: SearchBudget, simulations: usize) -> Self {
+        let tree = || {
+            let mut tree = SearchTree::new(board, simulations);
+            tree.early_stop = true;
+            tree
+        };
+        Self { search_trees: (0..trees.max(1)).map(|_| tree()).collect(), budget }
+    }
+
+    // Root move statistics of every tree, for spotting where the trees disagree
+    pub fn tree_stats(&self) -> Vec<Vec<MoveStats>> {
+        self.search_trees.iter().map(|tree| tree.root_moves()).collect()
+    }
+
+    pub fn merged_stats(&self) -> Vec<MoveStats> {
+        let mut merged: Vec<MoveStats> = vec![];
+        for stats in self.tree_stats().into_iter().flatten() {
+            match merged.iter_mut().find(|m| m.index == stats.index) {
+                Some(m) => {
+                    m.record = Record {
+                        wins: m.record.wins + stats.record.wins,
+                        draws: m.record.draws + stats.record.draws,
+                        played: m.record.played + stats.record.played,
+                    };
+                    // A proof from any tree holds for all of them
+                    m.result = m.result.or(stats.result);
+                }
+                None => merged.push(stats),
+            }
+        }
+        merged.sort_by_key(|m| m.index);
+        merged
+    }
+
+    fn choose_move(&self, board: Board) -> Result<usize, SearchError> {
+        mcst::choose_from(&self.merged_stats(), board.active_player)
+    }
+}
+
+impl Agent for Monties {
+    fn select_move(&mut self, board: Board) -> Result<usize, SearchError> {
+        let budget = self.budget;
+        thread::scope(|scope| {
+            for tree in self.search_trees.iter_mut() {
+                scope.spawn(move || tree.search(budget));
+            }
+        });
+
+        self.choose_move(board)
+    }
+
+    fn record_move(&mut self, index: usize, board: Board) -> Board {
+        for tree in self.search_trees.iter_mut() {
+            tree.record_move(index, board);
+        }
+        board
+    }
+
+    fn taken_back(&mut self, board: Board) {
+        for tree in self.search_trees.iter_mut() {
+            tree.reset_root(board);
+        }
+    }
+
+    // Each tree gets its own stream so they still search differently
+    fn seed(&mut self, seed: u64) {
+        let mut seeds = StdRng::seed_from_u64(seed);
+        for tree in self.search_trees.iter_mut() {
+            tree.rng = StdRng::seed_from_u64(seeds.gen());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn merged_stats_sum_every_tree() {
+        // Arrange
+        let board = Board::default();
+        let mut monties = Monties::new(board, 3, SearchBudget::Iterations(5), 5);
+
+        // Act
+        monties.select_move(board).unwrap();
+
+        // Assert
+        let per_tree: u64 = monties
+            .tree_stats()
+            .iter()
+            .flatten()
+            .map(|m| m.record.played)
+            .sum();
+        let merged: u64 = monties.merged_stats().iter().map(|m| m.record.played).sum();
+        assert_eq!(monties.tree_stats().len(), 3);
+        assert_eq!(per_tree, merged);
+        assert_eq!(monties.merged_stats().len(), 7);
+    }
+
+    #[test]
+    pub fn ensemble_blocks_opponent_win() {
+        // Arrange
+        let b = Board::from_position("7/7/7/7/7/yyy2bb b").unwrap();
+        let mut monties = Monties::new(b, 4, SearchBudget::Iterations(10), 5);
+
+        // Act
+        let m = monties.select_move(b).unwrap();
+
+        // Assert
+        assert_eq!(m, 3);
+    }
+}
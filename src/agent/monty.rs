@@ -1,37 +1,73 @@
-use crate::{
-    game::board::Board,
-    mcst::{node::Node, SearchTree},
-};
-
-use super::Agent;
-
-pub struct Monty {
-    search_tree: SearchTree,
-    iterations: usize
-}
-
-impl Monty {
-    pub fn new(board: Board, iterations: usize, simulations: usize) -> Self {
-        Self {
-            search_tree: SearchTree::new(board, simulations),
-            iterations
-        }
-    }
-}
-
-impl Agent for Monty {
-    fn select_move(&mut self, board: Board) -> usize {
-        // self.search_tree.print_state();
-
-        for i in 0..self.iterations {
-            self.search_tree.iterate();
-        }
-
-        // self.search_tree.print_state();
-        self.search_tree.choose_move()
-    }
-
-    fn record_move(&mut self, index: usize, board: Board) -> Board {
-        self.search_tree.record_move(index, board)
-    }
-}
+use crate::{
+    game::board::Board,
+    mcst::{budget::SearchBudget, error::SearchError, playout::PlayoutPolicy, report::SearchReport, selection::SelectionPolicy, SearchTree},
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::Agent;
+
+pub struct Monty {
+    search_tree: SearchTree<Board>,
+    budget: SearchBudget
+}
+
+impl Monty {
+    // Runs every one of the iterations
+    pub fn new(board: Board, iterations: usize, simulations: usize) -> Self {
+        Self::with_budget(board, SearchBudget::Iterations(iterations), simulations).with_early_stop(false)
+    }
+
+    // Stops early once the best move can't change within the budget
+    pub fn with_budget(board: Board, budget: SearchBudget, simulations: usize) -> Self {
+        Self {
+            search_tree: SearchTree::new(board, simulations),
+            budget
+        }
+        .with_early_stop(true)
+    }
+
+    pub fn with_selection_policy(mut self, policy: Box<dyn SelectionPolicy>) -> Self {
+        self.search_tree.selection_policy = policy;
+        self
+    }
+
+    // With early stopping off a playout budget is always used up, unless the root is proven
+    pub fn with_early_stop(mut self, early_stop: bool) -> Self {
+        self.search_tree.early_stop = early_stop;
+        self
+    }
+
+    pub fn report(&self) -> SearchReport {
+        self.search_tree.report()
+    }
+
+    // For exporting or inspecting the tree behind a move
+    pub fn search_tree(&self) -> &SearchTree<Board> {
+        &self.search_tree
+    }
+
+    pub fn with_playout_policy(mut self, policy: Box<dyn PlayoutPolicy<Board>>) -> Self {
+        self.search_tree.playout_policy = policy;
+        self
+    }
+}
+
+impl Agent for Monty {
+    fn select_move(&mut self, _board: Board) -> Result<usize, SearchError> {
+        self.search_tree.search(self.budget);
+        self.search_tree.try_choose_move()
+    }
+
+    fn record_move(&mut self, index: usize, board: Board) -> Board {
+        self.search_tree.record_move(index, board)
+    }
+
+    fn taken_back(&mut self, board: Board) {
+        self.search_tree.reset_root(board);
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.search_tree.rng = StdRng::seed_from_u64(seed);
+    }
+}
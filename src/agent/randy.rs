@@ -1,20 +1,58 @@
-use crate::game::board::Board;
-
-use super::Agent;
-use rand::RngCore;
-
-pub struct Randy;
-
-impl Agent for Randy {
-    fn select_move(&mut self, board: crate::game::board::Board) -> usize {
-        let mut rand = rand::thread_rng();
-
-        let moves = board.get_moves();
-        let rand_index: usize = rand.next_u64() as usize % moves.len();
-        moves[rand_index]
-    }
-
-    fn record_move(&mut self, index: usize, board: Board) -> crate::game::board::Board {
-        board
-    }
-}
\ No newline at end of file
+use crate::{game::board::Board, mcst::error::SearchError};
+
+use super::Agent;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+pub struct Randy {
+    rng: StdRng,
+}
+
+impl Randy {
+    pub fn new() -> Self {
+        Self { rng: StdRng::from_entropy() }
+    }
+}
+
+impl Default for Randy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Agent for Randy {
+    fn select_move(&mut self, board: Board) -> Result<usize, SearchError> {
+        let moves = board.get_moves();
+        if moves.is_empty() {
+            return Err(SearchError::NoLegalMoves);
+        }
+        Ok(moves[self.rng.gen_range(0..moves.len())])
+    }
+
+    fn record_move(&mut self, _index: usize, board: Board) -> Board {
+        board
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn same_seed_plays_the_same_moves() {
+        // Arrange
+        let (mut first, mut second) = (Randy::new(), Randy::new());
+        first.seed(42);
+        second.seed(42);
+
+        // Act
+        let first_moves: Vec<usize> = (0..20).map(|_| first.select_move(Board::default()).unwrap()).collect();
+        let second_moves: Vec<usize> = (0..20).map(|_| second.select_move(Board::default()).unwrap()).collect();
+
+        // Assert
+        assert_eq!(first_moves, second_moves);
+    }
+}
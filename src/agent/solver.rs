@@ -0,0 +1,380 @@
+use std::{
+    cmp::Ordering,
+    time::{Duration, Instant},
+};
+
+use crate::game::{
+    board::{Bitboard, Board, ConnectBoard, CONNECT, HEIGHT, WIDTH},
+    player::Player,
+};
+
+use crate::mcst::error::SearchError;
+
+use super::Agent;
+
+const MATE: i32 = 100_000;
+// Any score beyond this is a forced result, below it is a heuristic
+const MATE_BOUND: i32 = MATE - 1_000;
+const TABLE_SIZE: usize = 1 << 18;
+const TIME_CHECK_INTERVAL: u64 = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Score {
+    // Plies until the game ends, counting the winning move
+    Win(u32),
+    Loss(u32),
+    Draw,
+    // Search ran out of depth before proving a result
+    Heuristic(i32),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Solution {
+    pub best_move: usize,
+    pub score: Score,
+    pub depth: u32,
+    pub nodes: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TableEntry {
+    key: (Bitboard, Bitboard),
+    depth: u32,
+    // Mate scores are stored relative to the entry's position, not the search root
+    score: i32,
+    bound: Bound,
+    best_move: usize,
+}
+
+// Negamax alpha-beta solver with iterative deepening. Searching to the end of the game gives exact results,
+// otherwise only forced wins and losses are proven.
+pub struct ConnectSolver<const W: usize, const H: usize, const N: usize> {
+    max_depth: u32,
+    time_limit: Option<Duration>,
+    table: Vec<Option<TableEntry>>,
+    nodes: u64,
+    deadline: Option<Instant>,
+    aborted: bool,
+}
+
+pub type Solver = ConnectSolver<WIDTH, HEIGHT, CONNECT>;
+
+impl<const W: usize, const H: usize, const N: usize> ConnectSolver<W, H, N> {
+    pub fn new(max_depth: u32, time_limit: Option<Duration>) -> Self {
+        Self {
+            max_depth,
+            time_limit,
+            table: vec![None; TABLE_SIZE],
+            nodes: 0,
+            deadline: None,
+            aborted: false,
+        }
+    }
+
+    // Searches without depth or time limits
+    pub fn exact() -> Self {
+        Self::new((W * H) as u32, None)
+    }
+
+    pub fn solve(&mut self, board: ConnectBoard<W, H, N>) -> Solution {
+        let moves = board.get_moves();
+        if board.winner.is_some() || moves.is_empty() {
+            panic!("Attempting to solve a finished game {board:?}");
+        }
+
+        self.nodes = 0;
+        self.aborted = false;
+        self.deadline = self.time_limit.map(|limit| Instant::now() + limit);
+
+        let remaining = (W * H) as u32 - (board.yellow_bb | board.blue_bb).count_ones();
+        let max_depth = self.max_depth.min(remaining).max(1);
+
+        let mut best = Solution {
+            best_move: order_moves(board, None)[0],
+            score: Score::Heuristic(0),
+            depth: 0,
+            nodes: 0,
+        };
+        for depth in 1..=max_depth {
+            let score = self.negamax(board, depth, 0, -MATE, MATE);
+            if self.aborted {
+                break;
+            }
+            let best_move = self
+                .probe(board)
+                .map(|entry| entry.best_move)
+                .unwrap_or(best.best_move);
+            best = Solution {
+                best_move,
+                score: to_score(score, depth == remaining),
+                depth,
+                nodes: self.nodes,
+            };
+
+            if !matches!(best.score, Score::Heuristic(_)) {
+                break;
+            }
+        }
+        best.nodes = self.nodes;
+        best
+    }
+
+    fn negamax(&mut self, board: ConnectBoard<W, H, N>, depth: u32, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        self.nodes += 1;
+        if self.nodes % TIME_CHECK_INTERVAL == 0 {
+            if let Some(deadline) = self.deadline {
+                self.aborted |= Instant::now() >= deadline;
+            }
+        }
+        if self.aborted {
+            return 0;
+        }
+
+        match board.winner {
+            Some(Player::NoPlayer) => return 0,
+            Some(_) => return -(MATE - ply),
+            None => (),
+        }
+        if depth == 0 {
+            return evaluate(board);
+        }
+
+        let original_alpha = alpha;
+        let mut table_move = None;
+        if let Some(entry) = self.probe(board) {
+            table_move = Some(entry.best_move);
+            if entry.depth >= depth {
+                let score = from_table(entry.score, ply);
+                let cutoff = match entry.bound {
+                    Bound::Exact => true,
+                    Bound::Lower => score >= beta,
+                    Bound::Upper => score <= alpha,
+                };
+                if cutoff {
+                    return score;
+                }
+            }
+        }
+
+        let mut best_score = -MATE;
+        let mut best_move = None;
+        for column in order_moves(board, table_move) {
+            let score = -self.negamax(board.play_move(column), depth - 1, ply + 1, -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score || best_move.is_none() {
+                best_score = score;
+                best_move = Some(column);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.store(TableEntry {
+            key: (board.yellow_bb, board.blue_bb),
+            depth,
+            score: to_table(best_score, ply),
+            bound,
+            best_move: best_move.unwrap(),
+        });
+        best_score
+    }
+
+    fn probe(&self, board: ConnectBoard<W, H, N>) -> Option<TableEntry> {
+        let key = (board.yellow_bb, board.blue_bb);
+        match self.table[table_index(key)] {
+            Some(entry) if entry.key == key => Some(entry),
+            _ => None,
+        }
+    }
+
+    fn store(&mut self, entry: TableEntry) {
+        let slot = &mut self.table[table_index(entry.key)];
+        match slot {
+            // Keep deeper results for the same position
+            Some(existing) if existing.key == entry.key && existing.depth > entry.depth => (),
+            _ => *slot = Some(entry),
+        }
+    }
+}
+
+impl Agent for Solver {
+    fn select_move(&mut self, board: Board) -> Result<usize, SearchError> {
+        if board.winner.is_some() {
+            return Err(SearchError::NoLegalMoves);
+        }
+        Ok(self.solve(board).best_move)
+    }
+
+    fn record_move(&mut self, _index: usize, board: Board) -> Board {
+        board
+    }
+}
+
+fn table_index(key: (Bitboard, Bitboard)) -> usize {
+    let mixed = (key.0 ^ key.1.rotate_left(64)).wrapping_mul(0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C835);
+    (mixed >> 64) as usize % TABLE_SIZE
+}
+
+fn to_table(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s > MATE_BOUND => s + ply,
+        s if s < -MATE_BOUND => s - ply,
+        s => s,
+    }
+}
+
+fn from_table(score: i32, ply: i32) -> i32 {
+    match score {
+        s if s > MATE_BOUND => s - ply,
+        s if s < -MATE_BOUND => s + ply,
+        s => s,
+    }
+}
+
+fn to_score(score: i32, searched_to_end: bool) -> Score {
+    match score {
+        s if s > MATE_BOUND => Score::Win((MATE - s) as u32),
+        s if s < -MATE_BOUND => Score::Loss((MATE + s) as u32),
+        0 if searched_to_end => Score::Draw,
+        s => Score::Heuristic(s),
+    }
+}
+
+// Immediate wins first, then forced blocks, then moves creating the most threats, breaking ties towards the centre.
+// Moves that hand the opponent a win are searched last.
+fn order_moves<const W: usize, const H: usize, const N: usize>(
+    board: ConnectBoard<W, H, N>,
+    table_move: Option<usize>,
+) -> Vec<usize> {
+    let mover = board.active_player;
+    let opponent_wins = winning_columns(board.pass());
+
+    let mut scored: Vec<(usize, i32)> = board
+        .get_moves()
+        .into_iter()
+        .map(|column| {
+            let child = board.play_move(column);
+            let centre = -((2 * column as i32) - (W as i32 - 1)).abs();
+            let score = if child.winner == Some(mover) {
+                i32::MAX
+            } else if Some(column) == table_move {
+                i32::MAX - 1
+            } else if opponent_wins.contains(&column) {
+                i32::MAX - 2
+            } else if !winning_columns(child).is_empty() {
+                // Opponent can win straight away
+                i32::MIN + centre.abs()
+            } else {
+                winning_columns(child.pass()).len() as i32 * 100 + centre
+            };
+            (column, score)
+        })
+        .collect();
+
+    scored.sort_by(|a, b| match b.1.cmp(&a.1) {
+        Ordering::Equal => a.0.cmp(&b.0),
+        o => o,
+    });
+    scored.into_iter().map(|(column, _)| column).collect()
+}
+
+// Columns the side to move could play to win immediately
+fn winning_columns<const W: usize, const H: usize, const N: usize>(board: ConnectBoard<W, H, N>) -> Vec<usize> {
+    if board.winner.is_some() {
+        return vec![];
+    }
+    board
+        .get_moves()
+        .into_iter()
+        .filter(|column| board.play_move(*column).winner == Some(board.active_player))
+        .collect()
+}
+
+// Centre control for the side to move, kept well inside the mate bound
+fn evaluate<const W: usize, const H: usize, const N: usize>(board: ConnectBoard<W, H, N>) -> i32 {
+    board.centre_control() + 10 * (winning_columns(board).len() as i32 - winning_columns(board.pass()).len() as i32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn solver_takes_immediate_win() {
+        // Arrange
+        let b = Board::from_position("7/7/7/7/7/yyy1bbb y").unwrap();
+        let mut solver = Solver::new(6, None);
+
+        // Act
+        let solution = solver.solve(b);
+
+        // Assert
+        assert_eq!(solution.best_move, 3);
+        assert_eq!(solution.score, Score::Win(1));
+    }
+
+    #[test]
+    pub fn solver_blocks_and_sees_the_forced_loss_otherwise() {
+        // Arrange
+        let b = Board::default().play_move(3).play_move(0).play_move(3).play_move(0).play_move(3);
+        let mut solver = Solver::new(4, None);
+
+        // Act
+        let solution = solver.solve(b);
+        let elsewhere: Vec<Score> = [0, 1, 2, 4, 5, 6].iter().map(|c| solver.solve(b.play_move(*c)).score).collect();
+
+        // Assert
+        assert_eq!(solution.best_move, 3);
+        assert!(!matches!(solution.score, Score::Loss(_)));
+        assert!(elsewhere.iter().all(|score| *score == Score::Win(1)));
+    }
+
+    #[test]
+    pub fn solver_finds_win_in_three() {
+        // Arrange
+        // Yellow on 2 and 3 with both 1 and 4 open on the bottom row
+        let b = Board::default().play_move(2).play_move(2).play_move(3).play_move(3);
+        let mut solver = Solver::new(8, None);
+
+        // Act
+        let solution = solver.solve(b);
+
+        // Assert
+        assert!(solution.best_move == 1 || solution.best_move == 4);
+        assert_eq!(solution.score, Score::Win(3));
+    }
+
+    #[test]
+    pub fn solver_proves_exact_results_on_small_board() {
+        // Arrange
+        let drawn = ConnectBoard::<3, 3, 3>::default().play_move(1).play_move(1);
+        let lost = ConnectBoard::<3, 3, 3>::default().play_move(1).play_move(0).play_move(2).play_move(0);
+        let mut solver = ConnectSolver::<3, 3, 3>::exact();
+
+        // Act
+        let drawn_solution = solver.solve(drawn);
+        let lost_solution = solver.solve(lost);
+
+        // Assert
+        assert_eq!(drawn_solution.score, Score::Draw);
+        assert_eq!(lost_solution.score, Score::Loss(4));
+    }
+}
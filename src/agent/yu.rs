@@ -1,29 +1,121 @@
-use std::{io::{self, stdin}, f32::consts::E};
-
-use crate::game::board::Board;
-
-use super::Agent;
-use rand::RngCore;
-
-pub struct Yu;
-
-impl Agent for Yu {
-    fn select_move(&mut self, board: crate::game::board::Board) -> usize {
-        let moves = board.get_moves();
-        board.print_board();
-        println!("{board:?}");
-        println!("Select move : {moves:?}");
-        let mut entry = String::new();
-        loop {
-            stdin().read_line(&mut entry);
-            match entry.trim().parse() {
-                Ok(r) => return r,
-                Err(e) => println!("Invalid move please select another")
-            }
-        }
-    }
-
-    fn record_move(&mut self, index: usize, board: Board) -> crate::game::board::Board {
-        board
-    }
-}
\ No newline at end of file
+use std::io::{self, stdin, BufRead, BufReader};
+
+use crate::{
+    game::{board::Board, history::History},
+    mcst::error::SearchError,
+};
+
+use super::{Agent, Decision};
+
+// Moves come from whoever is at the keyboard
+pub struct Yu {
+    // The game from the first move this agent saw
+    history: Option<History<Board>>,
+    input: Box<dyn BufRead>,
+}
+
+impl Default for Yu {
+    fn default() -> Self {
+        Self::with_input(Box::new(BufReader::new(stdin())))
+    }
+}
+
+impl Yu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Reads moves from somewhere other than stdin
+    pub fn with_input(input: Box<dyn BufRead>) -> Self {
+        Self { history: None, input }
+    }
+}
+
+impl Agent for Yu {
+    fn select_move(&mut self, board: Board) -> Result<usize, SearchError> {
+        loop {
+            if let Decision::Move(m) = self.decide(board)? {
+                return Ok(m);
+            }
+            println!("Take-backs aren't available here");
+        }
+    }
+
+    fn record_move(&mut self, index: usize, board: Board) -> Board {
+        match self.history.as_mut() {
+            Some(history) => {
+                history.play(index);
+            }
+            None => self.history = board.undo_move(index).and_then(|start| History::from_moves(start, &[index])),
+        }
+        board
+    }
+
+    fn taken_back(&mut self, board: Board) {
+        if let Some(history) = self.history.as_mut() {
+            let undone = (history.board().turn - board.turn) as usize;
+            history.jump(history.ply().saturating_sub(undone));
+        }
+    }
+
+    // Columns are typed 1-indexed, as in the move list. Typing "u" takes back your last move along with the
+    // reply to it. Input that fails or runs out forfeits rather than asking forever
+    fn decide(&mut self, board: Board) -> Result<Decision, SearchError> {
+        let moves = board.get_moves();
+        board.print_board();
+        println!("{board:?}");
+        if let Some(history) = self.history.as_ref().filter(|h| h.ply() > 0) {
+            println!("Moves so far: {}", history.to_moves());
+        }
+        let columns: Vec<usize> = moves.iter().map(|m| m + 1).collect();
+        println!("Select move : {columns:?} or u to undo");
+        let mut entry = String::new();
+        loop {
+            entry.clear();
+            match self.input.read_line(&mut entry) {
+                Ok(0) => return Err(SearchError::Input(io::ErrorKind::UnexpectedEof)),
+                Ok(_) => (),
+                Err(e) => return Err(SearchError::Input(e.kind())),
+            }
+            match entry.trim() {
+                "u" | "undo" => return Ok(Decision::TakeBack),
+                m => match m.parse::<usize>() {
+                    Ok(r) if r > 0 && moves.contains(&(r - 1)) => return Ok(Decision::Move(r - 1)),
+                    _ => println!("Invalid move please select another"),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn typing(text: &str) -> Yu {
+        Yu::with_input(Box::new(Cursor::new(text.to_string())))
+    }
+
+    #[test]
+    pub fn illegal_entries_are_asked_again() {
+        // Arrange
+        let board = Board::from_moves("111111").unwrap();
+
+        // Act
+        let decision = typing("1\n9\nx\n2\n").decide(board);
+
+        // Assert
+        assert_eq!(decision, Ok(Decision::Move(1)));
+    }
+
+    #[test]
+    pub fn closed_input_forfeits_instead_of_waiting() {
+        // Act
+        let decision = typing("9\n").decide(Board::default());
+
+        // Assert
+        assert_eq!(decision, Err(SearchError::Input(io::ErrorKind::UnexpectedEof)));
+    }
+}
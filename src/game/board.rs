@@ -1,452 +1,937 @@
-use colored::Colorize;
-use core::fmt::Debug;
-use log::debug;
-
-use super::player::Player;
-
-pub const WIDTH: usize = 7;
-pub const HEIGHT: usize = 6;
-pub const MAX_INDEX: usize = WIDTH * HEIGHT;
-
-#[derive(Copy, Clone)]
-pub struct Board {
-    pub yellow_bb: u64,
-    pub blue_bb: u64,
-    pub column_pieces: [usize; WIDTH],
-    pub active_player: Player,
-    pub winner: Option<Player>,
-    pub turn: u32
-}
-
-impl PartialEq for Board {
-    fn eq(&self, other: &Self) -> bool {
-        self.yellow_bb == other.yellow_bb && self.blue_bb == other.blue_bb
-    }
-}
-
-impl Default for Board {
-    fn default() -> Self {
-        Self {
-            yellow_bb: 0,
-            blue_bb: 0,
-            column_pieces: [0; WIDTH],
-            active_player: Player::Yellow,
-            winner: None,
-            turn: 0
-        }
-    }
-}
-
-impl Debug for Board {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("Board")
-            .field(&self.active_player)
-            .field(&self.yellow_bb)
-            .field(&self.blue_bb)
-            .field(&self.winner)
-            .finish()
-    }
-}
-
-impl Board {
-    pub fn get_moves(self) -> Vec<usize> {
-        let mut available_moves: Vec<usize> = vec![];
-
-        for column in 0..WIDTH {
-            if self.column_pieces[column] != 6 {
-                available_moves.push(column);
-            }
-        }
-
-        available_moves
-    }
-
-    fn cell_empty(self, x: usize, y: usize) -> bool {
-        self.column_pieces[x] < y
-    }
-
-    pub fn play_move(self, column: usize) -> Board {
-        let mut n_b = self.clone();
-        let row = self.column_pieces[column];
-        let index = row * WIDTH + column; // Double check this
-        if index >= MAX_INDEX {
-            println!("attempting to play move: {column} in state {self:?}");
-            self.print_board();
-            println!("{:?}", self.get_moves());
-            println!("{:?}", self.column_pieces);
-            panic!("invalid move {column}");
-        }
-        if self.active_player == Player::Yellow {
-            n_b.yellow_bb ^= 1 << index;
-        } else {
-            n_b.blue_bb ^= 1 << index;
-        }
-        n_b.column_pieces[column] += 1;
-        n_b.update_winner(index);
-        n_b.active_player = self.active_player.invert();
-        n_b.turn += 1;
-        n_b
-    }
-
-    fn update_winner(&mut self, index: usize) {
-        let bb = match self.active_player {
-            Player::Yellow => self.yellow_bb,
-            Player::Blue => self.blue_bb,
-            Player::NoPlayer => panic!("Board Active Player should never be {}", Player::NoPlayer)
-        };
-
-        debug!(
-            "checking winner {} : v{}/h{}/d{}",
-            self.active_player,
-            check_vertical(bb, index),
-            check_horizontal(bb, index),
-            check_diagonals(bb, index)
-        );
-        if bb.count_ones() > 3
-            && (check_vertical(bb, index)
-                || check_horizontal(bb, index)
-                || check_diagonals(bb, index))
-        {
-            self.winner = Some(self.active_player)
-        }
-
-        if self.winner.is_none() && self.get_moves().len() == 0 {
-            self.winner = Some(Player::NoPlayer)
-        }
-    }
-
-    fn get_rank_str(&self, rank: usize) -> String {
-        let mut str = String::default();
-        let inverted_rank = HEIGHT - 1 - rank;
-        for i in 0..WIDTH {
-            let inverted_file = WIDTH - 1 - i;
-            if self.blue_bb >> (inverted_file + inverted_rank * WIDTH) & 0b1 > 0 {
-                str = format!("{}{}", str, &"0".blue());
-            } else if self.yellow_bb >> (inverted_file + inverted_rank * WIDTH) & 0b1 > 0 {
-                str = format!("{}{}", str, &"0".yellow());
-            } else {
-                str = format!("{}{}", str, &"X".dimmed());
-            }
-        }
-        str
-    }
-
-    pub fn print_board(&self) {
-        for rank in 0..HEIGHT {
-            println!("{}", self.get_rank_str(rank));
-        }
-    }
-
-    pub fn setup(yellow_bb: u64, blue_bb: u64, column_pieces: [usize; WIDTH]) -> Self {
-        Self {
-            yellow_bb: yellow_bb,
-            blue_bb: blue_bb,
-            active_player: if blue_bb.count_ones() == yellow_bb.count_ones() { Player::Yellow } else { Player::Blue },
-            column_pieces,
-            winner: None,
-            turn: yellow_bb.count_ones() + blue_bb.count_ones() + 1
-        }
-    }
-}
-
-fn format_bb(bb: u64) -> String {
-    let mut r: String = "".to_string();
-
-    for i in 0..HEIGHT {
-        let rank = HEIGHT - 1 - i;
-        r += &format!("{:#09b}\n", (bb >> (rank * WIDTH) & 127));
-    }
-
-    r
-}
-
-fn check_diagonals(bb: u64, index: usize) -> bool {
-    let mut start_pos = index;
-    let rank: usize = index / WIDTH;
-    for step_tl_br in 1..rank + 1 {
-        let offset = step_tl_br * WIDTH + step_tl_br;
-        if index < offset {
-            break;
-        }
-        let pos = index - offset;
-
-        if pos / WIDTH != rank - step_tl_br {
-            break;
-        }
-        if bb >> pos & 1 == 1 {
-            start_pos = pos;
-        } else {
-            break;
-        }
-    }
-    if start_pos % WIDTH <= 3 {
-        let relevant_bb = bb >> start_pos;
-        debug!(
-            "TL_BR: \t Starting pos {start_pos}\n{}",
-            format_bb(relevant_bb)
-        );
-
-        if relevant_bb & 0x1010101 == 0x1010101 {
-            return true;
-        }
-    } else {
-        debug!("TL_BR: \t Skipping due to wrapping {start_pos}")
-    }
-
-    for step_bl_tr in 1..rank + 1 {
-        let pos = index - (step_bl_tr * WIDTH - step_bl_tr);
-
-        if pos / WIDTH != rank - step_bl_tr {
-            break;
-        }
-        if bb >> pos & 1 == 1 {
-            start_pos = pos;
-        } else {
-            break;
-        }
-    }
-
-    if start_pos % WIDTH > 3 {
-        let relevant_bb = if start_pos < 6 {
-            bb << (7 - start_pos)
-        } else {
-            bb >> (start_pos - 6)
-        };
-        relevant_bb & 0x1041040 == 0x1041040
-    } else {
-        debug!("BL_TR: \t Skipping due to wrapping {start_pos}");
-        false
-    }
-}
-
-fn check_horizontal(mut bb: u64, index: usize) -> bool {
-    bb = bb & (0x7F << WIDTH * (index / WIDTH));
-    let mut start_pos = index;
-    let horizontal_position = index % WIDTH;
-
-    for right_index in 1..horizontal_position + 1 {
-        let check_pos = index - right_index;
-        if bb >> check_pos & 1 == 0 {
-            break;
-        } else {
-            start_pos = check_pos;
-        }
-    }
-
-    let relevant_bb = bb >> start_pos;
-    relevant_bb & 0xF == 0xF
-}
-
-fn check_vertical(bb: u64, index: usize) -> bool {
-    if index < 3 * WIDTH {
-        return false;
-    }
-    bb >> (index - (WIDTH * 3)) & 0x204081 == 0x204081
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-
-    #[test]
-    pub fn check_vertical_valid_win() {
-        let file = 4;
-        let bb = 0x204081 << file;
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-
-        board.print_board();
-        assert!(check_vertical(bb, (file + WIDTH * 3)))
-    }
-
-    #[test]
-    pub fn check_vertical_below_4th_row_fails() {
-        let file = 6;
-        let bb = 0x4081 << file;
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-
-        board.print_board();
-        assert!(!check_vertical(bb, file + WIDTH * 2))
-    }
-
-    #[test]
-    pub fn check_vertical_above_4th_row_but_missing_a_position() {
-        let file = 5;
-        let bb = 0x10200080 << file;
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-
-        board.print_board();
-        assert!(!check_vertical(bb, file + WIDTH * 4))
-    }
-
-    #[test]
-    pub fn check_vertical_win_with_noise() {
-        let file = 5;
-        let bb = 0x14606188 << file;
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-
-        board.print_board();
-        assert!(check_vertical(bb, file + WIDTH * 4))
-    }
-
-    #[test]
-    pub fn check_horizontal_valid_win() {
-        let bb = 0x78;
-        let board = Board::setup(0, bb, [0; WIDTH]);
-
-        board.print_board();
-        assert!(check_horizontal(bb, 5));
-        assert!(check_horizontal(bb, 6));
-        assert!(check_horizontal(bb, 4));
-        assert!(check_horizontal(bb, 3))
-    }
-
-    #[test]
-    pub fn check_horizontal_no_wrapping_wins() {
-        let bb = 0xF0;
-        let board = Board::setup(0, bb, [0; WIDTH]);
-
-        board.print_board();
-        assert!(!check_horizontal(bb, 7));
-        assert!(!check_horizontal(bb, 6));
-        assert!(!check_horizontal(bb, 5));
-        assert!(!check_horizontal(bb, 4))
-    }
-
-    #[test]
-    pub fn check_horizontal_only_win_on_left_side() {
-        let bb = 0x1EC000;
-        let board = Board::setup(0, bb, [0; WIDTH]);
-
-        board.print_board();
-        assert!(!check_horizontal(bb, 14));
-        assert!(check_horizontal(bb, 18));
-    }
-
-    #[test]
-    pub fn check_horizontal_case_0() {
-        let bb = 0x2020F65;
-
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-        board.print_board();
-        assert!(check_horizontal(bb, 8));
-    }
-
-    #[test]
-    pub fn check_horizontal_case_1() {
-        let bb = 0x8F;
-
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-        board.print_board();
-        assert!(check_horizontal(bb, 1));
-    }
-
-    #[test]
-    pub fn check_diagonal_valid_starting_in_bottom_corner_0() {
-        let bb = 0x1010101;
-        let board = Board::setup(0, bb, [0; WIDTH]);
-
-        board.print_board();
-        assert!(check_diagonals(bb, 0));
-    }
-
-    #[test]
-    pub fn check_diagonal_valid_starting_in_bottom_corner_6() {
-        let bb = 0x1041040;
-        let board = Board::setup(0, bb, [0; WIDTH]);
-
-        board.print_board();
-        assert!(check_diagonals(bb, 24));
-    }
-
-    #[test]
-    pub fn check_diagonal_bl_not_on_file_0() {
-        let bb = 0x1041040 << 6;
-        let board = Board::setup(0, bb, [0; WIDTH]);
-
-        board.print_board();
-        assert!(check_diagonals(bb, 30));
-    }
-
-    #[test]
-    pub fn check_diagonal_br_not_on_file_0() {
-        let bb = 0x1010101 << 10;
-        let board = Board::setup(0, bb, [0; WIDTH]);
-
-        board.print_board();
-        assert!(check_diagonals(bb, 26));
-    }
-
-    #[test]
-    pub fn check_diagonal_bl_wrapping_fails() {
-        let bb = 0x1041040 >> 5;
-        let board = Board::setup(0, bb, [0; WIDTH]);
-
-        board.print_board();
-        assert!(!check_diagonals(bb, 13));
-    }
-
-    #[test]
-    pub fn check_diagonal_br_wrapping_fails() {
-        let bb = 0x1010101 << 12;
-        let board = Board::setup(0, bb, [0; WIDTH]);
-
-        board.print_board();
-        assert!(!check_diagonals(bb, 28));
-    }
-
-    #[test]
-    pub fn check_diagonal_case_0() {
-        let bb = 0x8219;
-
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-        board.print_board();
-        assert!(!check_diagonals(bb, 9));
-    }
-
-    #[test]
-    pub fn check_diagonal_case_1() {
-        let bb = 0x10099;
-
-        let board = Board::setup(0, bb, [0; WIDTH]);
-        board.print_board();
-        assert!(!check_diagonals(bb, 7));
-    }
-
-    #[test]
-    pub fn check_diagonal_case_2() {
-        let bb = 13314539663852;
-
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-        board.print_board();
-        assert!(!check_diagonals(bb, 19));
-    }
-
-    #[test]
-    pub fn check_diagonal_case_3() {
-        let bb = 0x104104;
-
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-        board.print_board();
-        assert!(!check_diagonals(bb, 3));
-    }
-    #[test]
-    pub fn check_diagonal_case_4() {
-        let bb = 0x208208;
-
-        let board = Board::setup(bb, 0, [0; WIDTH]);
-        board.print_board();
-        assert!(check_diagonals(bb, 4));
-    }
-
-    #[test]
-    pub fn update_winner_move_leading_to_draw() {
-        // Arrange
-        let b = Board::setup(890452430364, 1308570825187, [6, 6, 6, 6, 6, 6, 5]);
-
-        // Act
-        let r = b.play_move(6);
-
-        // Assert
-        assert_eq!(r.winner, Some(Player::NoPlayer));
-    }
-}
+use colored::Colorize;
+use core::fmt::Debug;
+use log::debug;
+
+use super::{
+    error::{MoveError, PositionError},
+    history::History,
+    move_set::MoveSet,
+    player::Player,
+    result::GameResult,
+    Game,
+};
+
+pub const WIDTH: usize = 7;
+pub const HEIGHT: usize = 6;
+pub const CONNECT: usize = 4;
+pub const MAX_INDEX: usize = WIDTH * HEIGHT;
+
+// Cells are stored column by column: bit `column * (H + 1) + row`, with row 0 at the bottom. The extra row on
+// top of each column is always empty, so shifting a line off the top of one column never joins it to the next
+pub type Bitboard = u128;
+
+// Standard 7x6 connect-4
+pub type Board = ConnectBoard<WIDTH, HEIGHT, CONNECT>;
+
+#[derive(Copy, Clone)]
+pub struct ConnectBoard<const W: usize, const H: usize, const N: usize> {
+    pub yellow_bb: Bitboard,
+    pub blue_bb: Bitboard,
+    pub column_pieces: [usize; W],
+    pub active_player: Player,
+    pub winner: Option<Player>,
+    pub turn: u32,
+}
+
+impl<const W: usize, const H: usize, const N: usize> PartialEq for ConnectBoard<W, H, N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.yellow_bb == other.yellow_bb && self.blue_bb == other.blue_bb
+    }
+}
+
+impl<const W: usize, const H: usize, const N: usize> Default for ConnectBoard<W, H, N> {
+    fn default() -> Self {
+        let () = Self::DIMENSIONS_FIT;
+        Self {
+            yellow_bb: 0,
+            blue_bb: 0,
+            column_pieces: [0; W],
+            active_player: Player::Yellow,
+            winner: None,
+            turn: 0,
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, const N: usize> Debug for ConnectBoard<W, H, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Board")
+            .field(&self.active_player)
+            .field(&self.yellow_bb)
+            .field(&self.blue_bb)
+            .field(&self.winner)
+            .finish()
+    }
+}
+
+impl<const W: usize, const H: usize, const N: usize> ConnectBoard<W, H, N> {
+    const DIMENSIONS_FIT: () = assert!(
+        W * (H + 1) <= Bitboard::BITS as usize && W <= u64::BITS as usize && N > 1 && N <= W && N <= H,
+        "board dimensions must fit the bitboard and allow a connection"
+    );
+
+    // Bits between one cell and the next in a column, across a row, and along each diagonal
+    const STRIDE: usize = H + 1;
+    const DIRECTIONS: [usize; 4] = [1, H + 1, H + 2, H];
+    const BOTTOM_ROW: Bitboard = line_mask(H + 1, W);
+    const TOP_ROW: Bitboard = Self::BOTTOM_ROW << (H - 1);
+    const CELLS: Bitboard = Self::BOTTOM_ROW * ((1 << H) - 1);
+
+    pub const fn cell(column: usize, row: usize) -> Bitboard {
+        1 << (column * Self::STRIDE + row)
+    }
+
+    pub fn get_moves(self) -> Vec<usize> {
+        self.move_set().collect()
+    }
+
+    // Columns with space left, read off the top row
+    pub fn move_set(&self) -> MoveSet {
+        let mut open = !(self.yellow_bb | self.blue_bb) & Self::TOP_ROW;
+        let mut moves = 0;
+        while open != 0 {
+            moves |= 1 << (open.trailing_zeros() as usize / Self::STRIDE);
+            open &= open - 1;
+        }
+        MoveSet::from_bits(moves)
+    }
+
+    // Panics on an illegal move, see try_play_move for moves that haven't been checked
+    pub fn play_move(self, column: usize) -> Self {
+        match self.try_play_move(column) {
+            Ok(n_b) => n_b,
+            Err(e) => panic!("invalid move {column}: {e} in state {self:?}"),
+        }
+    }
+
+    pub fn try_play_move(self, column: usize) -> Result<Self, MoveError> {
+        if column >= W {
+            return Err(MoveError::OutOfRange(column));
+        }
+        if self.winner.is_some() {
+            return Err(MoveError::GameOver);
+        }
+        let mut n_b = self;
+        let row = self.column_pieces[column];
+        if row >= H {
+            return Err(MoveError::ColumnFull(column));
+        }
+        if self.active_player == Player::Yellow {
+            n_b.yellow_bb |= Self::cell(column, row);
+        } else {
+            n_b.blue_bb |= Self::cell(column, row);
+        }
+        n_b.column_pieces[column] += 1;
+        n_b.update_winner();
+        n_b.active_player = self.active_player.invert();
+        n_b.turn += 1;
+        Ok(n_b)
+    }
+
+    // Takes back the last move, which the caller names since the board doesn't keep its moves. None if the
+    // column's top piece can't have been the last one played
+    pub fn undo_move(self, column: usize) -> Option<Self> {
+        let row = self.column_pieces.get(column)?.checked_sub(1)?;
+        let bit = Self::cell(column, row);
+        // Found from the piece counts rather than the side to move, which a pass would have flipped
+        let mover = if self.yellow_bb.count_ones() > self.blue_bb.count_ones() { Player::Yellow } else { Player::Blue };
+        let own = if mover == Player::Yellow { self.yellow_bb } else { self.blue_bb };
+        // The piece must be the mover's, and the game can't already have been won without it
+        if own & bit == 0 || Self::has_connection(own & !bit) {
+            return None;
+        }
+        let mut p_b = self;
+        match mover {
+            Player::Yellow => p_b.yellow_bb &= !bit,
+            _ => p_b.blue_bb &= !bit,
+        }
+        p_b.column_pieces[column] = row;
+        p_b.active_player = mover;
+        p_b.winner = None;
+        p_b.turn -= 1;
+        Some(p_b)
+    }
+
+    // Builds a position from 1-indexed columns, one digit per move such as "4453", or separated by commas
+    // or spaces such as "4,4,10,3" when a column takes more than one digit
+    pub fn from_moves(moves: &str) -> Result<Self, PositionError> {
+        let is_separator = |c: char| c == ',' || c.is_whitespace();
+        let columns: Vec<&str> = match moves.contains(is_separator) {
+            true => moves.split(is_separator).filter(|m| !m.is_empty()).collect(),
+            false => moves.matches(|_| true).collect(),
+        };
+        let mut board = Self::default();
+        for (ply, m) in columns.iter().enumerate() {
+            let column = m.chars().try_fold(0usize, |column, c| match c.to_digit(10) {
+                Some(digit) => Ok(column.saturating_mul(10).saturating_add(digit as usize)),
+                None => Err(PositionError::InvalidCharacter(c)),
+            })?;
+            board = column
+                .checked_sub(1)
+                .ok_or(MoveError::OutOfRange(column))
+                .and_then(|column| board.try_play_move(column))
+                .map_err(|error| PositionError::IllegalMove { ply: ply + 1, error })?;
+        }
+        Ok(board)
+    }
+
+
+    // Rows from the top separated by '/', 'y' and 'b' for pieces and digits for runs of empty cells,
+    // then the side to move: "7/7/7/7/3b3/3y3 y"
+    pub fn to_position(&self) -> String {
+        let mut rows = vec![];
+        for row in (0..H).rev() {
+            let mut cells = String::new();
+            let mut empty = 0;
+            for column in 0..W {
+                let bit = Self::cell(column, row);
+                let piece = match (self.yellow_bb & bit != 0, self.blue_bb & bit != 0) {
+                    (true, _) => 'y',
+                    (_, true) => 'b',
+                    _ => {
+                        empty += 1;
+                        continue;
+                    }
+                };
+                if empty > 0 {
+                    cells.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                cells.push(piece);
+            }
+            if empty > 0 {
+                cells.push_str(&empty.to_string());
+            }
+            rows.push(cells);
+        }
+        let side = if self.active_player == Player::Blue { 'b' } else { 'y' };
+        format!("{} {side}", rows.join("/"))
+    }
+
+    pub fn from_position(position: &str) -> Result<Self, PositionError> {
+        let (grid, side) = position.trim().split_once(' ').ok_or(PositionError::MissingSideToMove)?;
+        let active_player = match side.trim() {
+            "y" => Player::Yellow,
+            "b" => Player::Blue,
+            "" => return Err(PositionError::MissingSideToMove),
+            other => return Err(PositionError::InvalidCharacter(other.chars().next().unwrap())),
+        };
+
+        let rows: Vec<&str> = grid.split('/').collect();
+        if rows.len() != H {
+            return Err(PositionError::RowCount(rows.len()));
+        }
+        let (mut yellow_bb, mut blue_bb): (Bitboard, Bitboard) = (0, 0);
+        for (i, cells) in rows.iter().enumerate() {
+            let row = H - 1 - i;
+            let mut column: usize = 0;
+            // Runs of empty cells can take more than one digit on boards wider than nine columns
+            let mut empty: usize = 0;
+            for c in cells.chars() {
+                if let Some(digit) = c.to_digit(10) {
+                    if empty == 0 && digit == 0 {
+                        return Err(PositionError::InvalidCharacter(c));
+                    }
+                    empty = empty.saturating_mul(10).saturating_add(digit as usize);
+                    continue;
+                }
+                column = column.saturating_add(std::mem::take(&mut empty));
+                match c {
+                    'y' | 'b' if column < W => {
+                        let bit = Self::cell(column, row);
+                        if c == 'y' {
+                            yellow_bb |= bit;
+                        } else {
+                            blue_bb |= bit;
+                        }
+                        column += 1;
+                    }
+                    'y' | 'b' => column += 1,
+                    _ => return Err(PositionError::InvalidCharacter(c)),
+                }
+            }
+            column = column.saturating_add(empty);
+            if column != W {
+                return Err(PositionError::RowLength { row: i + 1, length: column });
+            }
+        }
+        Self::from_bitboards(yellow_bb, blue_bb, active_player)
+    }
+
+    // Checks the position could come up in a game and derives the rest of the state from it
+    fn from_bitboards(yellow_bb: Bitboard, blue_bb: Bitboard, active_player: Player) -> Result<Self, PositionError> {
+        let () = Self::DIMENSIONS_FIT;
+        if yellow_bb & blue_bb != 0 {
+            return Err(PositionError::Overlapping);
+        }
+        let occupied = yellow_bb | blue_bb;
+        if occupied & !Self::CELLS != 0 {
+            return Err(PositionError::OffBoard);
+        }
+        let mut column_pieces = [0; W];
+        for (column, pieces) in column_pieces.iter_mut().enumerate() {
+            let stack = occupied >> (column * Self::STRIDE) & ((1 << H) - 1);
+            *pieces = stack.trailing_ones() as usize;
+            if stack >> *pieces != 0 {
+                return Err(PositionError::FloatingPiece { column });
+            }
+        }
+
+        let (yellow, blue) = (yellow_bb.count_ones(), blue_bb.count_ones());
+        let expected = if yellow == blue { Player::Yellow } else { Player::Blue };
+        if (yellow != blue && yellow != blue + 1) || active_player != expected {
+            return Err(PositionError::PieceCount);
+        }
+
+        // Only the player who just moved can have connected
+        let winner = match (Self::has_connection(yellow_bb), Self::has_connection(blue_bb)) {
+            (true, true) => return Err(PositionError::ImpossibleWin),
+            (true, false) if active_player == Player::Blue => Some(Player::Yellow),
+            (false, true) if active_player == Player::Yellow => Some(Player::Blue),
+            (false, false) if yellow + blue == (W * H) as u32 => Some(Player::NoPlayer),
+            (false, false) => None,
+            _ => return Err(PositionError::ImpossibleWin),
+        };
+
+        Ok(Self {
+            yellow_bb,
+            blue_bb,
+            column_pieces,
+            active_player,
+            winner,
+            turn: yellow + blue,
+        })
+    }
+
+    // Shifting the board along a direction and ANDing it with itself N - 1 times leaves the start of every line
+    fn has_connection(bb: Bitboard) -> bool {
+        Self::DIRECTIONS.iter().any(|&step| {
+            let mut lines = bb;
+            for k in 1..N {
+                lines &= bb >> (k * step);
+            }
+            lines != 0
+        })
+    }
+
+    // Hands the turn to the other player without placing a piece, used when looking for threats
+    pub fn pass(self) -> Self {
+        let mut n_b = self;
+        n_b.active_player = self.active_player.invert();
+        n_b
+    }
+
+    // Pieces weighted by how central their column is, from the side to move's point of view
+    pub fn centre_control(&self) -> i32 {
+        let (own, other) = match self.active_player {
+            Player::Yellow => (self.yellow_bb, self.blue_bb),
+            _ => (self.blue_bb, self.yellow_bb),
+        };
+        let mut score = 0;
+        for column in 0..W {
+            let weight = (W - (2 * column).abs_diff(W - 1)) as i32;
+            let pieces = |bb: Bitboard| (bb >> (column * Self::STRIDE) & ((1 << H) - 1)).count_ones() as i32;
+            score += weight * (pieces(own) - pieces(other));
+        }
+        score
+    }
+
+    // Only the player who just moved can have connected
+    fn update_winner(&mut self) {
+        let bb = match self.active_player {
+            Player::Yellow => self.yellow_bb,
+            Player::Blue => self.blue_bb,
+            Player::NoPlayer => panic!("Board Active Player should never be {}", Player::NoPlayer)
+        };
+
+        if Self::has_connection(bb) {
+            debug!("{} connected\n{}", self.active_player, Self::format_bb(bb));
+            self.winner = Some(self.active_player)
+        } else if self.yellow_bb | self.blue_bb == Self::CELLS {
+            self.winner = Some(Player::NoPlayer)
+        }
+    }
+
+    // Ranks count down from the top, columns run left to right from column 0
+    fn get_rank_str(&self, rank: usize) -> String {
+        let mut str = String::default();
+        let inverted_rank = H - 1 - rank;
+        for file in 0..W {
+            let bit = Self::cell(file, inverted_rank);
+            if self.blue_bb & bit != 0 {
+                str = format!("{}{}", str, &"0".blue());
+            } else if self.yellow_bb & bit != 0 {
+                str = format!("{}{}", str, &"0".yellow());
+            } else {
+                str = format!("{}{}", str, &"X".dimmed());
+            }
+        }
+        str
+    }
+
+    pub fn print_board(&self) {
+        for rank in 0..H {
+            println!("{}", self.get_rank_str(rank));
+        }
+        // Column numbers as they are typed, the last digit only on wide boards
+        println!("{}", (1..=W).map(|c| (c % 10).to_string()).collect::<String>());
+    }
+
+    // Everything else about the position is worked out from the bitboards, which must describe a reachable game
+    pub fn setup(yellow_bb: Bitboard, blue_bb: Bitboard) -> Result<Self, PositionError> {
+        let active_player = if yellow_bb.count_ones() == blue_bb.count_ones() { Player::Yellow } else { Player::Blue };
+        Self::from_bitboards(yellow_bb, blue_bb, active_player)
+    }
+
+    // One line per row from the top, leftmost column first
+    fn format_bb(bb: Bitboard) -> String {
+        let mut r: String = "".to_string();
+
+        for i in 0..H {
+            let rank = H - 1 - i;
+            let cells: String = (0..W).map(|column| if bb & Self::cell(column, rank) != 0 { '1' } else { '.' }).collect();
+            r += &format!("{cells}\n");
+        }
+
+        r
+    }
+}
+
+impl<const W: usize, const H: usize, const N: usize> Game for ConnectBoard<W, H, N> {
+    const ACTIONS: usize = W;
+    type Key = (Bitboard, Bitboard);
+
+    fn legal_moves(&self) -> Vec<usize> {
+        self.get_moves()
+    }
+
+    fn move_set(&self) -> MoveSet {
+        ConnectBoard::move_set(self)
+    }
+
+    fn apply_move(&self, index: usize) -> Self {
+        self.play_move(index)
+    }
+
+    fn result(&self) -> Option<GameResult> {
+        self.winner.map(|winner| match winner {
+            Player::Yellow | Player::Blue => GameResult::Win(winner),
+            Player::NoPlayer => GameResult::Draw,
+        })
+    }
+
+    fn side_to_move(&self) -> Player {
+        self.active_player
+    }
+
+    fn key(&self) -> Self::Key {
+        (self.yellow_bb, self.blue_bb)
+    }
+
+    // Central columns take part in more lines so are weighted up
+    fn priors(&self) -> Vec<f32> {
+        let mut priors = vec![0.0; W];
+        for column in self.get_moves() {
+            priors[column] = (W - (2 * column).abs_diff(W - 1)) as f32;
+        }
+        let total: f32 = priors.iter().sum();
+        priors.iter().map(|p| p / total).collect()
+    }
+
+    fn evaluate(&self) -> f32 {
+        self.centre_control() as f32
+    }
+}
+
+// n bits spaced `step` apart, starting at bit 0
+const fn line_mask(step: usize, n: usize) -> Bitboard {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < n {
+        mask |= 1 << (i * step);
+        i += 1;
+    }
+    mask
+}
+
+impl<const W: usize, const H: usize, const N: usize> History<ConnectBoard<W, H, N>> {
+    // The moves played so far in from_moves notation, separated by commas once columns need two digits
+    pub fn to_moves(&self) -> String {
+        let columns = self.moves().iter().map(|c| (c + 1).to_string());
+        match W <= 9 {
+            true => columns.collect(),
+            false => columns.collect::<Vec<_>>().join(","),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Bitboards here are written a row at a time, bit row * W + column, and moved into the board's layout
+    fn rows<const W: usize, const H: usize, const N: usize>(bb: Bitboard) -> Bitboard {
+        (0..W * H)
+            .filter(|i| bb >> i & 1 == 1)
+            .fold(0, |cells, i| cells | ConnectBoard::<W, H, N>::cell(i % W, i / W))
+    }
+
+    fn grid(bb: Bitboard) -> Bitboard {
+        rows::<WIDTH, HEIGHT, CONNECT>(bb)
+    }
+
+    fn connected(bb: Bitboard) -> bool {
+        Board::has_connection(grid(bb))
+    }
+
+    #[test]
+    pub fn check_vertical_valid_win() {
+        let file = 4;
+        let bb = 0x204081 << file;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb))
+    }
+
+    #[test]
+    pub fn check_vertical_below_4th_row_fails() {
+        let file = 6;
+        let bb = 0x4081 << file;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(!connected(bb))
+    }
+
+    #[test]
+    pub fn check_vertical_above_4th_row_but_missing_a_position() {
+        let file = 5;
+        let bb = 0x10200080 << file;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(!connected(bb))
+    }
+
+    #[test]
+    pub fn check_vertical_win_with_noise() {
+        let file = 5;
+        let bb = 0x14606188 << file;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb))
+    }
+
+    #[test]
+    pub fn check_horizontal_valid_win() {
+        let bb = 0x78;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb))
+    }
+
+    #[test]
+    pub fn check_horizontal_no_wrapping_wins() {
+        let bb = 0xF0;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(!connected(bb))
+    }
+
+    #[test]
+    pub fn check_horizontal_only_win_on_left_side() {
+        let bb = 0x1EC000;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb));
+        // The gap on the left leaves only three once the right hand end is gone
+        assert!(!connected(bb & !(1 << 20)));
+    }
+
+    #[test]
+    pub fn check_horizontal_case_0() {
+        let bb = 0x2020F65;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb));
+    }
+
+    #[test]
+    pub fn check_horizontal_case_1() {
+        let bb = 0x8F;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_valid_starting_in_bottom_corner_0() {
+        let bb = 0x1010101;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_valid_starting_in_bottom_corner_6() {
+        let bb = 0x1041040;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_bl_not_on_file_0() {
+        let bb = 0x1041040 << 6;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_br_not_on_file_0() {
+        let bb = 0x1010101 << 10;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_bl_wrapping_fails() {
+        let bb = 0x1041040 >> 5;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(!connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_br_wrapping_fails() {
+        let bb = 0x1010101 << 12;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(!connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_case_0() {
+        let bb = 0x8219;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(!connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_case_1() {
+        let bb = 0x10099;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(!connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_case_2() {
+        let bb = 13314539663852;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(!connected(bb));
+    }
+
+    #[test]
+    pub fn check_diagonal_case_3() {
+        let bb = 0x104104;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(!connected(bb));
+    }
+    #[test]
+    pub fn check_diagonal_case_4() {
+        let bb = 0x208208;
+
+        println!("{}", Board::format_bb(grid(bb)));
+        assert!(connected(bb));
+    }
+
+    #[test]
+    pub fn connect_3_on_6x5_horizontal_win() {
+        let b = ConnectBoard::<6, 5, 3>::setup(rows::<6, 5, 3>(0b11), rows::<6, 5, 3>(0b11 << 6)).unwrap();
+
+        let r = b.play_move(2);
+
+        assert_eq!(r.winner, Some(Player::Yellow));
+    }
+
+    #[test]
+    pub fn connect_5_on_9x7_needs_five() {
+        type Board9x7 = ConnectBoard<9, 7, 5>;
+        let four_vertical: Bitboard = (0..4).map(|row| Board9x7::cell(0, row)).sum();
+        let five_vertical = four_vertical | Board9x7::cell(0, 4);
+
+        assert!(!Board9x7::has_connection(four_vertical));
+        assert!(Board9x7::has_connection(five_vertical));
+    }
+
+    #[test]
+    pub fn connect_5_on_9x7_diagonals_reach_top_edge() {
+        type Board9x7 = ConnectBoard<9, 7, 5>;
+        // Row 2 column 4 up-right to row 6 column 8, and up-left to row 6 column 0
+        let diagonal: Bitboard = (0..5).map(|k| Board9x7::cell(4 + k, 2 + k)).sum();
+        let anti_diagonal: Bitboard = (0..5).map(|k| Board9x7::cell(4 - k, 2 + k)).sum();
+
+        assert!(Board9x7::has_connection(diagonal));
+        assert!(Board9x7::has_connection(anti_diagonal));
+        assert!(!Board9x7::has_connection(anti_diagonal & !Board9x7::cell(0, 6)));
+        assert!(!Board9x7::has_connection(diagonal & !Board9x7::cell(8, 6)));
+    }
+
+    #[test]
+    pub fn full_8x7_board_is_a_draw_not_an_overflow() {
+        type Board8x7 = ConnectBoard<8, 7, 4>;
+        let mut b = Board8x7::default();
+
+        // Fill column pairs in an order that never connects four
+        for pair in [0, 2, 4, 6] {
+            for row in 0..7 {
+                let (first, second) = if row % 4 < 2 { (pair, pair + 1) } else { (pair + 1, pair) };
+                b = b.play_move(first).play_move(second);
+            }
+        }
+
+        assert_eq!(b.winner, Some(Player::NoPlayer));
+        assert_eq!(b.turn, 56);
+    }
+
+    #[test]
+    pub fn move_set_skips_full_columns() {
+        // Arrange
+        let b = Board::from_moves("111111777777").unwrap();
+
+        // Act
+        let moves = b.move_set();
+
+        // Assert
+        assert_eq!(moves.bits(), 0b0111110);
+        assert_eq!(b.get_moves(), vec![1, 2, 3, 4, 5]);
+        assert!(Board::from_moves("111111").unwrap().play_move(1).move_set().contains(1));
+    }
+
+    #[test]
+    pub fn try_play_move_rejects_illegal_moves() {
+        // Arrange
+        let full_first_column = Board::from_moves("111111").unwrap();
+        let won = [0, 1, 0, 1, 0, 1, 0].iter().fold(Board::default(), |b, m| b.play_move(*m));
+
+        // Act & Assert
+        assert_eq!(full_first_column.try_play_move(0).err(), Some(MoveError::ColumnFull(0)));
+        assert_eq!(Board::default().try_play_move(7).err(), Some(MoveError::OutOfRange(7)));
+        assert_eq!(won.try_play_move(5).err(), Some(MoveError::GameOver));
+        assert!(Board::default().try_play_move(3).is_ok());
+    }
+
+    #[test]
+    pub fn from_moves_plays_one_indexed_columns() {
+        // Act
+        let board = Board::from_moves("4453").unwrap();
+
+        // Assert
+        assert_eq!(board, Board::default().play_move(3).play_move(3).play_move(4).play_move(2));
+        assert_eq!(Board::from_moves("4 4, 5,3"), Ok(board));
+        assert_eq!(History::from_moves(Board::default(), &[3, 3, 4, 2]).unwrap().to_moves(), "4453");
+    }
+
+    #[test]
+    pub fn moves_round_trip_on_a_wide_board() {
+        // Arrange
+        type Wide = ConnectBoard<12, 6, 4>;
+        let history = History::from_moves(Wide::default(), &[11, 0, 9, 3]).unwrap();
+
+        // Act
+        let moves = history.to_moves();
+        let parsed = Wide::from_moves(&moves);
+
+        // Assert
+        assert_eq!(moves, "12,1,10,4");
+        assert_eq!(parsed, Ok(history.board()));
+        assert_eq!(
+            Wide::from_moves("1,13"),
+            Err(PositionError::IllegalMove { ply: 2, error: MoveError::OutOfRange(12) })
+        );
+    }
+
+    #[test]
+    pub fn from_moves_reports_the_bad_move() {
+        assert_eq!(Board::from_moves("44x"), Err(PositionError::InvalidCharacter('x')));
+        assert_eq!(
+            Board::from_moves("408"),
+            Err(PositionError::IllegalMove { ply: 2, error: MoveError::OutOfRange(0) })
+        );
+        assert_eq!(
+            Board::from_moves("1111111"),
+            Err(PositionError::IllegalMove { ply: 7, error: MoveError::ColumnFull(0) })
+        );
+        assert_eq!(
+            Board::from_moves("12121215"),
+            Err(PositionError::IllegalMove { ply: 8, error: MoveError::GameOver })
+        );
+    }
+
+    #[test]
+    pub fn undo_move_restores_the_previous_position() {
+        // Arrange
+        let before = Board::from_moves("121212").unwrap();
+        let won = before.play_move(0);
+
+        // Act
+        let undone = won.undo_move(0).unwrap();
+
+        // Assert
+        assert_eq!(undone, before);
+        assert_eq!(undone.winner, None);
+        assert_eq!(undone.active_player, Player::Yellow);
+        assert_eq!(undone.turn, 6);
+        assert_eq!(undone.column_pieces, before.column_pieces);
+        assert_eq!(Board::from_moves("4").unwrap().undo_move(3), Some(Board::default()));
+        assert_eq!(Board::default().undo_move(0), None);
+        assert_eq!(Board::default().undo_move(7), None);
+    }
+
+    #[test]
+    pub fn undo_move_rejects_a_column_the_last_move_wasnt_in() {
+        // Arrange
+        let board = Board::from_moves("44553").unwrap();
+        // Yellow connects in column 1, with another yellow piece on top of column 3
+        let won = Board::from_moves("321217171").unwrap();
+
+        // Act & Assert
+        // Yellow moved last, so blue's piece on top of column 4 can't be taken back
+        assert_eq!(board.undo_move(3), None);
+        assert_eq!(board.undo_move(2), Board::from_moves("4455").ok());
+        assert_eq!(won.winner, Some(Player::Yellow));
+        assert_eq!(won.undo_move(2), None);
+        assert_eq!(won.undo_move(0).map(|b| b.turn), Some(8));
+    }
+
+    #[test]
+    pub fn printed_ranks_put_the_first_column_on_the_left() {
+        // Arrange
+        colored::control::set_override(false);
+        let board = Board::from_moves("1").unwrap();
+
+        // Act
+        let bottom = board.get_rank_str(HEIGHT - 1);
+
+        // Assert
+        assert_eq!(bottom, "0XXXXXX");
+    }
+
+    #[test]
+    pub fn position_string_round_trips() {
+        // Arrange
+        let board = Board::from_moves("4453").unwrap();
+
+        // Act
+        let position = board.to_position();
+        let parsed = Board::from_position(&position).unwrap();
+
+        // Assert
+        assert_eq!(position, "7/7/7/7/3b3/2byy2 y");
+        assert_eq!(parsed, board);
+        assert_eq!(parsed.column_pieces, board.column_pieces);
+        assert_eq!(parsed.active_player, Player::Yellow);
+        assert_eq!(parsed.turn, 4);
+        assert_eq!(Board::from_position(&Board::default().to_position()).unwrap(), Board::default());
+    }
+
+    #[test]
+    pub fn position_string_round_trips_on_a_wide_board() {
+        // Arrange
+        type Wide = ConnectBoard<10, 6, 4>;
+        let board = Wide::default().play_move(9).play_move(0).play_move(4);
+
+        // Act
+        let empty = Wide::from_position(&Wide::default().to_position());
+        let parsed = Wide::from_position(&board.to_position()).unwrap();
+
+        // Assert
+        assert_eq!(Wide::default().to_position(), "10/10/10/10/10/10 y");
+        assert_eq!(empty, Ok(Wide::default()));
+        assert_eq!(board.to_position(), "10/10/10/10/10/b3y4y b");
+        assert_eq!(parsed, board);
+        assert_eq!(parsed.column_pieces, board.column_pieces);
+        assert_eq!(Wide::from_position("10/10/10/10/10/01 y"), Err(PositionError::InvalidCharacter('0')));
+        assert_eq!(Wide::from_position("10/10/10/10/10/11 y"), Err(PositionError::RowLength { row: 6, length: 11 }));
+    }
+
+    #[test]
+    pub fn parsed_position_derives_the_winner() {
+        // Act
+        let won = Board::from_position("7/7/y6/yb5/yb5/yb5 b").unwrap();
+
+        // Assert
+        assert_eq!(won.winner, Some(Player::Yellow));
+        assert_eq!(won, Board::from_moves("1212121").unwrap());
+    }
+
+    #[test]
+    pub fn invalid_positions_are_rejected() {
+        assert_eq!(Board::from_position("7/7/7/7/7/7"), Err(PositionError::MissingSideToMove));
+        assert_eq!(Board::from_position("7/7/7/7/7 y"), Err(PositionError::RowCount(5)));
+        assert_eq!(Board::from_position("7/7/7/7/7/6 y"), Err(PositionError::RowLength { row: 6, length: 6 }));
+        assert_eq!(Board::from_position("7/7/7/7/7/3x3 y"), Err(PositionError::InvalidCharacter('x')));
+        assert_eq!(Board::from_position("7/7/7/7/y6/7 b"), Err(PositionError::FloatingPiece { column: 0 }));
+        assert_eq!(Board::from_position("7/7/7/7/7/yy5 b"), Err(PositionError::PieceCount));
+        assert_eq!(Board::from_position("7/7/7/7/7/y6 y"), Err(PositionError::PieceCount));
+        // Yellow connected but blue has moved since
+        assert_eq!(Board::from_position("7/7/yb5/yb5/yb5/yb5 y"), Err(PositionError::ImpossibleWin));
+    }
+
+    #[test]
+    pub fn setup_derives_the_rest_of_the_position() {
+        // Arrange
+        // Yellow on columns 1-3, blue on 6 and 7 of the bottom row
+        let b = Board::setup(grid(7), grid(96)).unwrap();
+
+        // Assert
+        assert_eq!(b.column_pieces, [1, 1, 1, 0, 0, 1, 1]);
+        assert_eq!(b.active_player, Player::Blue);
+        assert_eq!(b.winner, None);
+        assert_eq!(b.turn, 5);
+        assert_eq!(b.turn, Board::from_moves("17273").unwrap().turn);
+    }
+
+    #[test]
+    pub fn setup_derives_the_winner() {
+        // Arrange
+        let won = Board::from_moves("1212121").unwrap();
+
+        // Act
+        let b = Board::setup(won.yellow_bb, won.blue_bb).unwrap();
+
+        // Assert
+        assert_eq!(b.winner, Some(Player::Yellow));
+        assert_eq!(b.active_player, Player::Blue);
+    }
+
+    #[test]
+    pub fn setup_rejects_impossible_positions() {
+        assert_eq!(Board::setup(1, 1), Err(PositionError::Overlapping));
+        // The empty row above each column is off the board too
+        assert_eq!(Board::setup(Board::cell(0, HEIGHT), 0), Err(PositionError::OffBoard));
+        assert_eq!(Board::setup(1 << (WIDTH * (HEIGHT + 1)), 0), Err(PositionError::OffBoard));
+        assert_eq!(Board::setup(Board::cell(0, 1), Board::cell(1, 0)), Err(PositionError::FloatingPiece { column: 0 }));
+        assert_eq!(Board::setup(Board::cell(0, 0) | Board::cell(1, 0), 0), Err(PositionError::PieceCount));
+        assert_eq!(Board::setup(0, Board::cell(0, 0)), Err(PositionError::PieceCount));
+        // Yellow and blue both have four in a column
+        let two_winners = Board::setup(grid(0x204081), grid(0x204081 << 1));
+        assert_eq!(two_winners, Err(PositionError::ImpossibleWin));
+    }
+
+    #[test]
+    pub fn update_winner_move_leading_to_draw() {
+        // Arrange
+        let b = Board::setup(grid(890452430364), grid(1308570825187)).unwrap();
+
+        // Act
+        let r = b.play_move(6);
+
+        // Assert
+        assert_eq!(r.winner, Some(Player::NoPlayer));
+    }
+}
@@ -0,0 +1,58 @@
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoveError {
+    ColumnFull(usize),
+    OutOfRange(usize),
+    GameOver,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::ColumnFull(column) => write!(f, "column {column} is full"),
+            MoveError::OutOfRange(column) => write!(f, "column {column} is off the board"),
+            MoveError::GameOver => write!(f, "the game is already over"),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PositionError {
+    InvalidCharacter(char),
+    // Moves are numbered from 1
+    IllegalMove { ply: usize, error: MoveError },
+    RowCount(usize),
+    RowLength { row: usize, length: usize },
+    MissingSideToMove,
+    FloatingPiece { column: usize },
+    // Piece counts don't fit with whose turn it is
+    PieceCount,
+    // A cell holds both colours
+    Overlapping,
+    // Pieces on bits past the last row
+    OffBoard,
+    // Both players connected, or the game carried on after a win
+    ImpossibleWin,
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PositionError::InvalidCharacter(c) => write!(f, "unexpected character '{c}'"),
+            PositionError::IllegalMove { ply, error } => write!(f, "move {ply} is illegal: {error}"),
+            PositionError::RowCount(rows) => write!(f, "wrong number of rows: {rows}"),
+            PositionError::RowLength { row, length } => write!(f, "row {row} has {length} cells"),
+            PositionError::MissingSideToMove => write!(f, "missing side to move"),
+            PositionError::FloatingPiece { column } => write!(f, "column {} has a gap under a piece", column + 1),
+            PositionError::PieceCount => write!(f, "piece counts don't match the side to move"),
+            PositionError::Overlapping => write!(f, "a cell holds pieces of both colours"),
+            PositionError::OffBoard => write!(f, "pieces lie outside the board"),
+            PositionError::ImpossibleWin => write!(f, "no game could reach this position"),
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
@@ -0,0 +1,151 @@
+use super::Game;
+
+// A game record that can be stepped back and forth. Undoing keeps the later moves around for redo until a
+// different move is played
+#[derive(Clone, Debug)]
+pub struct History<G: Game> {
+    // Every position from the start, positions[ply] is the current one
+    positions: Vec<G>,
+    moves: Vec<usize>,
+    ply: usize,
+}
+
+impl<G: Game> History<G> {
+    pub fn new(start: G) -> Self {
+        Self { positions: vec![start], moves: vec![], ply: 0 }
+    }
+
+    // Plays out a list of moves, none if any of them is illegal
+    pub fn from_moves(start: G, moves: &[usize]) -> Option<Self> {
+        let mut history = Self::new(start);
+        for m in moves.iter() {
+            history.play(*m)?;
+        }
+        Some(history)
+    }
+
+    pub fn board(&self) -> G {
+        self.positions[self.ply]
+    }
+
+    pub fn start(&self) -> G {
+        self.positions[0]
+    }
+
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    // Plies that can be jumped to, including any that have been undone
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    // Moves leading to the current position
+    pub fn moves(&self) -> &[usize] {
+        &self.moves[..self.ply]
+    }
+
+    // Plays from the current position, dropping anything that was undone. None if the move isn't legal
+    pub fn play(&mut self, index: usize) -> Option<G> {
+        let board = self.board();
+        if board.result().is_some() || !board.legal_moves().contains(&index) {
+            return None;
+        }
+        self.positions.truncate(self.ply + 1);
+        self.moves.truncate(self.ply);
+        self.positions.push(board.apply_move(index));
+        self.moves.push(index);
+        self.ply += 1;
+        Some(self.board())
+    }
+
+    pub fn undo(&mut self) -> Option<G> {
+        self.jump(self.ply.checked_sub(1)?)
+    }
+
+    pub fn redo(&mut self) -> Option<G> {
+        self.jump(self.ply + 1)
+    }
+
+    pub fn jump(&mut self, ply: usize) -> Option<G> {
+        if ply > self.moves.len() {
+            return None;
+        }
+        self.ply = ply;
+        Some(self.board())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{board::Board, tic_tac_toe::TicTacToe};
+
+    use super::*;
+
+    #[test]
+    pub fn undo_and_redo_step_through_the_game() {
+        // Arrange
+        let mut history = History::new(Board::default());
+        history.play(3);
+        history.play(2);
+        history.play(3);
+
+        // Act
+        let undone = history.undo().unwrap();
+        let redone = history.redo();
+
+        // Assert
+        assert_eq!(undone, Board::from_moves("43").unwrap());
+        assert_eq!(redone, Some(Board::from_moves("434").unwrap()));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.moves(), &[3, 2, 3]);
+    }
+
+    #[test]
+    pub fn playing_after_undo_drops_the_undone_moves() {
+        // Arrange
+        let mut history = History::from_moves(Board::default(), &[3, 2, 3]).unwrap();
+        history.undo();
+        history.undo();
+
+        // Act
+        let board = history.play(4).unwrap();
+
+        // Assert
+        assert_eq!(board, Board::from_moves("45").unwrap());
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    pub fn jump_reaches_any_recorded_ply() {
+        // Arrange
+        let mut history = History::from_moves(TicTacToe::default(), &[4, 0, 8, 2]).unwrap();
+
+        // Act & Assert
+        assert_eq!(history.jump(0), Some(TicTacToe::default()));
+        assert_eq!(history.ply(), 0);
+        assert_eq!(history.jump(2), Some(TicTacToe::default().apply_move(4).apply_move(0)));
+        assert_eq!(history.moves(), &[4, 0]);
+        assert_eq!(history.jump(5), None);
+        assert_eq!(history.ply(), 2);
+        assert_eq!(history.undo().map(|_| history.ply()), Some(1));
+    }
+
+    #[test]
+    pub fn illegal_moves_are_not_recorded() {
+        // Arrange
+        let mut history = History::new(Board::from_moves("111111").unwrap());
+
+        // Act & Assert
+        assert_eq!(history.play(0), None);
+        assert_eq!(history.play(9), None);
+        assert!(history.is_empty());
+        assert_eq!(History::from_moves(Board::default(), &[0, 1, 0, 1, 0, 1, 0, 1]).map(|h| h.len()), None);
+    }
+}
@@ -1,3 +1,46 @@
-pub mod board;
-pub mod result;
-pub mod player;
\ No newline at end of file
+use core::{fmt::Debug, hash::Hash};
+
+use self::{move_set::MoveSet, player::Player, result::GameResult};
+
+pub mod board;
+pub mod error;
+pub mod history;
+pub mod move_set;
+pub mod result;
+pub mod player;
+pub mod tic_tac_toe;
+
+// A two player, perfect information game the search tree can be run over.
+// Moves are indices into a fixed action space of size `ACTIONS` (columns for connect-4, cells for tic-tac-toe)
+pub trait Game: Copy + PartialEq + Debug {
+    const ACTIONS: usize;
+    // Identifies a position regardless of the move order that reached it
+    type Key: Copy + Eq + Hash + Debug;
+
+    fn legal_moves(&self) -> Vec<usize>;
+    fn apply_move(&self, index: usize) -> Self;
+    fn result(&self) -> Option<GameResult>;
+    fn side_to_move(&self) -> Player;
+    fn key(&self) -> Self::Key;
+
+    // Legal moves as a bitmask, only for games with at most MoveSet::CAPACITY actions. Override to skip
+    // building the move list
+    fn move_set(&self) -> MoveSet {
+        self.legal_moves().into_iter().collect()
+    }
+
+    // Prior probability of each action being the best, used by PUCT selection. Uniform over legal moves by default
+    fn priors(&self) -> Vec<f32> {
+        let moves = self.legal_moves();
+        let mut priors = vec![0.0; Self::ACTIONS];
+        for m in moves.iter() {
+            priors[*m] = 1.0 / moves.len() as f32;
+        }
+        priors
+    }
+
+    // Static evaluation for the side to move, higher is better. Games without one score every position alike
+    fn evaluate(&self) -> f32 {
+        0.0
+    }
+}
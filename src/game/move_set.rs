@@ -0,0 +1,83 @@
+// Legal moves as a bitmask, bit i set when action i can be played. Iterates lowest action first without allocating
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MoveSet(u64);
+
+impl MoveSet {
+    // Games with more actions than this must go through Game::legal_moves
+    pub const CAPACITY: usize = u64::BITS as usize;
+
+    pub const fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(self) -> u64 {
+        self.0
+    }
+
+    pub const fn contains(self, index: usize) -> bool {
+        index < Self::CAPACITY && self.0 >> index & 1 == 1
+    }
+
+    pub const fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    // The n-th move in iteration order, for picking a random move without collecting them
+    pub fn nth_move(self, n: usize) -> Option<usize> {
+        self.into_iter().nth(n)
+    }
+}
+
+impl Iterator for MoveSet {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let index = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for MoveSet {}
+
+impl FromIterator<usize> for MoveSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        Self(iter.into_iter().fold(0, |bits, index| {
+            assert!(index < Self::CAPACITY, "action {index} doesn't fit in a move set");
+            bits | 1 << index
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn iterates_set_bits_in_order() {
+        // Arrange
+        let moves = MoveSet::from_bits(0b1010_0101);
+
+        // Act
+        let collected: Vec<usize> = moves.collect();
+
+        // Assert
+        assert_eq!(collected, vec![0, 2, 5, 7]);
+        assert_eq!(moves.len(), 4);
+        assert_eq!(moves.nth_move(2), Some(5));
+        assert!(moves.contains(7) && !moves.contains(1) && !moves.contains(200));
+        assert_eq!(collected.into_iter().collect::<MoveSet>(), moves);
+    }
+}
@@ -0,0 +1,91 @@
+use super::{move_set::MoveSet, player::Player, result::GameResult, Game};
+
+const LINES: [u16; 8] = [0x7, 0x38, 0x1C0, 0x49, 0x92, 0x124, 0x111, 0x54];
+const FULL: u16 = 0x1FF;
+
+// Yellow plays X and moves first, cells are indexed 0..9 left to right, top to bottom
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct TicTacToe {
+    pub x_bb: u16,
+    pub o_bb: u16,
+}
+
+impl TicTacToe {
+    pub fn setup(x_bb: u16, o_bb: u16) -> Self {
+        Self { x_bb, o_bb }
+    }
+
+    fn has_line(bb: u16) -> bool {
+        LINES.iter().any(|line| bb & line == *line)
+    }
+}
+
+impl Game for TicTacToe {
+    const ACTIONS: usize = 9;
+    type Key = (u16, u16);
+
+    fn legal_moves(&self) -> Vec<usize> {
+        self.move_set().collect()
+    }
+
+    fn move_set(&self) -> MoveSet {
+        if self.result().is_some() {
+            return MoveSet::default();
+        }
+        MoveSet::from_bits((!(self.x_bb | self.o_bb) & FULL) as u64)
+    }
+
+    fn apply_move(&self, index: usize) -> Self {
+        let mut n_b = *self;
+        match self.side_to_move() {
+            Player::Yellow => n_b.x_bb |= 1 << index,
+            _ => n_b.o_bb |= 1 << index,
+        }
+        n_b
+    }
+
+    fn result(&self) -> Option<GameResult> {
+        if Self::has_line(self.x_bb) {
+            Some(GameResult::Win(Player::Yellow))
+        } else if Self::has_line(self.o_bb) {
+            Some(GameResult::Win(Player::Blue))
+        } else if self.x_bb | self.o_bb == FULL {
+            Some(GameResult::Draw)
+        } else {
+            None
+        }
+    }
+
+    fn side_to_move(&self) -> Player {
+        if self.x_bb.count_ones() == self.o_bb.count_ones() {
+            Player::Yellow
+        } else {
+            Player::Blue
+        }
+    }
+
+    fn key(&self) -> Self::Key {
+        (self.x_bb, self.o_bb)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn completed_row_is_a_win() {
+        let board = TicTacToe::setup(0x3, 0x18).apply_move(2);
+
+        assert_eq!(board.result(), Some(GameResult::Win(Player::Yellow)));
+        assert!(board.legal_moves().is_empty());
+    }
+
+    #[test]
+    pub fn full_board_without_line_is_a_draw() {
+        // X O X / X O O / O X X
+        let board = TicTacToe::setup(0x18D, 0x72);
+
+        assert_eq!(board.result(), Some(GameResult::Draw));
+    }
+}
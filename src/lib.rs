@@ -0,0 +1,4 @@
+pub mod agent;
+pub mod game;
+pub mod mcst;
+pub mod tournament;
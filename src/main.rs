@@ -1,66 +1,94 @@
-use std::default;
-
-use crate::{
-    game::{
-        board::{Board, WIDTH},
-        result::GameResult, player::Player,
+use four_monties::{
+    agent::{monty::Monty, randy::Randy},
+    tournament::{
+        openings::OpeningSuite,
+        rating::{Anchor, Ratings},
+        round_robin::{Entrant, RoundRobin},
+        sprt::{Sprt, SprtMatch},
     },
-    agent::{monty::Monty, randy::Randy, yu::Yu},
-    tournament::Tournament, mcst::SearchTree,
 };
-use colored::Colorize;
-use rand::RngCore;
-
-mod game;
-mod mcst;
-mod agent;
-mod tournament;
 
 fn main() {
-    let mut yellow_wins = 0;
-    let mut yellow_win_aggregate_turns = 0;
-    let mut blue_wins = 0;
-    let mut blue_win_aggregate_turns = 0;
-    let mut draws = 0;
-    for i in 0..20 {
-        let board = Board::default();
-        let mut tournament = Tournament::new(Box::new(Monty::new(board, 50, 50)), Box::new(Monty::new(board, 100, 50)));
-
-        let board = tournament.play();
-
-        match board.winner {
-            Some(r) => println!(
-                "Game {i} Result: {} (Turn {})",
-                match r {
-                    Player::NoPlayer => {
-                        draws += 1;
-                        board.print_board();
-                        "Draw".to_string()
-                    }
-                    Player::Yellow => {
-                        yellow_wins += 1;
-                        yellow_win_aggregate_turns += board.turn;
-                        "Yellow Wins".yellow().to_string()
-                    }
-                    Player::Blue => {
-                        blue_wins += 1;
-                        blue_win_aggregate_turns += board.turn;
-                        board.print_board();
-                        "Blue Wins".blue().to_string()
-                    }
-                },
-                board.turn
-            ),
-            None => (),
-        }
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let sprt_mode = args.first().is_some_and(|arg| arg == "sprt");
+    if sprt_mode {
+        args.remove(0);
+    }
+    // Pass a seed to replay an earlier run
+    let master_seed = args.first().and_then(|arg| arg.parse().ok()).unwrap_or_else(rand::random);
+    println!("Seed {master_seed}");
+    // Then an opening book, otherwise the shipped balanced suite
+    let openings = match args.get(1) {
+        Some(path) => match OpeningSuite::load(path) {
+            Ok(openings) => openings,
+            Err(e) => {
+                eprintln!("{e}");
+                std::process::exit(1);
+            }
+        },
+        None => OpeningSuite::balanced(),
+    };
+    println!("{} openings", openings.len());
+    match sprt_mode {
+        true => regression_test(master_seed, openings),
+        false => round_robin(master_seed, openings),
     }
+}
 
+// Whether the candidate configuration is an improvement on the baseline, run with `sprt [seed] [opening book]`
+fn regression_test(master_seed: u64, openings: OpeningSuite) {
+    let baseline = Entrant::new("baseline", Box::new(|board| Box::new(Monty::new(board, 50, 50))));
+    let candidate = Entrant::new("candidate", Box::new(|board| Box::new(Monty::new(board, 100, 50))));
+    let sprt = Sprt::default();
     println!(
-        "Results {}\\{}\\{}",
-        yellow_wins.to_string().yellow(),
-        blue_wins.to_string().blue(),
-        draws.to_string().bold()
+        "SPRT elo0 {} elo1 {} alpha {} beta {}, bounds [{:.3}, {:.3}]",
+        sprt.elo0,
+        sprt.elo1,
+        sprt.alpha,
+        sprt.beta,
+        sprt.lower_bound(),
+        sprt.upper_bound()
     );
-    println!("Average yellow win turn {}", yellow_win_aggregate_turns / yellow_wins);
-    println!("Average blue win turn {}", blue_win_aggregate_turns / blue_wins);
+    let state = SprtMatch::new(baseline, candidate, sprt, 2000, master_seed)
+        .with_openings(openings)
+        .run(|state| println!("{state}"));
+    match state.decision {
+        Some(decision) => println!("{decision:?} after {} pairs", state.pairs()),
+        None => println!("No decision after {} pairs", state.pairs()),
+    }
+}
+
+fn round_robin(master_seed: u64, openings: OpeningSuite) {
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let round_robin = RoundRobin::new(10)
+        .with_seed(master_seed)
+        .with_threads(threads)
+        .with_openings(openings)
+        // Anchors the ratings so runs can be compared
+        .with_entrant(Entrant::new("randy", Box::new(|_| Box::new(Randy::new()))))
+        .with_entrant(Entrant::new("monty-50", Box::new(|board| Box::new(Monty::new(board, 50, 50)))))
+        .with_entrant(Entrant::new("monty-100", Box::new(|board| Box::new(Monty::new(board, 100, 50)))));
+
+    let names: Vec<&str> = round_robin.entrants().iter().map(|e| e.name.as_str()).collect();
+    let results = round_robin.run_with_progress(|progress| {
+        let game = &progress.game;
+        println!(
+            "[{}/{}] Game {}: {} v {}: {} (Turn {})",
+            progress.completed,
+            progress.total,
+            progress.number,
+            names[game.pairing.yellow],
+            names[game.pairing.blue],
+            game.outcome.result,
+            game.length()
+        );
+        if let Some(forfeit) = game.outcome.forfeit {
+            println!("Game {}: {} forfeits with {:?}", progress.number, forfeit.player, forfeit.reason);
+        }
+    });
+    println!("{results}");
+    match Ratings::fit(&results, &Anchor::default(), 1.0) {
+        Ok(ratings) => println!("{ratings}"),
+        Err(e) => println!("No ratings: {e}"),
+    }
 }
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchBudget {
+    Time(Duration),
+    Nodes(usize),
+    Playouts(usize),
+    Iterations(usize),
+}
+
+// Work done since a search started
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchProgress {
+    pub iterations: usize,
+    pub nodes: usize,
+    pub playouts: usize,
+    pub elapsed: Duration,
+}
+
+impl SearchBudget {
+    pub fn exhausted(&self, progress: &SearchProgress) -> bool {
+        match *self {
+            SearchBudget::Time(limit) => progress.elapsed >= limit,
+            SearchBudget::Nodes(limit) => progress.nodes >= limit,
+            SearchBudget::Playouts(limit) => progress.playouts >= limit,
+            SearchBudget::Iterations(limit) => progress.iterations >= limit,
+        }
+    }
+
+    // Estimate of the playouts still to come, extrapolated from the rate so far
+    pub fn remaining_playouts(&self, progress: &SearchProgress) -> f64 {
+        let (done, limit) = match *self {
+            SearchBudget::Time(limit) => (progress.elapsed.as_secs_f64(), limit.as_secs_f64()),
+            SearchBudget::Nodes(limit) => (progress.nodes as f64, limit as f64),
+            SearchBudget::Playouts(limit) => return limit.saturating_sub(progress.playouts) as f64,
+            SearchBudget::Iterations(limit) => (progress.iterations as f64, limit as f64),
+        };
+        if done <= 0.0 {
+            return f64::INFINITY;
+        }
+        (limit - done).max(0.0) * progress.playouts as f64 / done
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn remaining_playouts_extrapolates_from_rate() {
+        // Arrange
+        let progress = SearchProgress {
+            iterations: 10,
+            nodes: 70,
+            playouts: 350,
+            elapsed: Duration::from_millis(100),
+        };
+
+        // Assert
+        assert_eq!(SearchBudget::Iterations(20).remaining_playouts(&progress), 350.0);
+        assert_eq!(SearchBudget::Playouts(500).remaining_playouts(&progress), 150.0);
+        assert!((SearchBudget::Time(Duration::from_millis(300)).remaining_playouts(&progress) - 700.0).abs() < 1e-6);
+        assert_eq!(SearchBudget::Nodes(70).remaining_playouts(&progress), 0.0);
+        assert!(SearchBudget::Nodes(70).exhausted(&progress));
+    }
+}
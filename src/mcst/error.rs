@@ -0,0 +1,25 @@
+use core::fmt;
+use std::io;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchError {
+    // The root has not been searched, so there are no statistics to choose from
+    Unexpanded,
+    NoLegalMoves,
+    InvalidMove(usize),
+    // A move had to be read from input that failed or was closed
+    Input(io::ErrorKind),
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::Unexpanded => write!(f, "the root has not been searched"),
+            SearchError::NoLegalMoves => write!(f, "there are no legal moves from the root"),
+            SearchError::InvalidMove(index) => write!(f, "move {index} is not legal from the root"),
+            SearchError::Input(kind) => write!(f, "couldn't read a move: {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for SearchError {}
@@ -0,0 +1,246 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    sync::Arc,
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::game::{result::GameResult, Game};
+
+use super::{
+    node::ArcNode,
+    record::Record,
+    selection::Candidate,
+    valid_move::ValidMove,
+    SearchTree,
+};
+
+// Limits on how much of the tree gets written out
+#[derive(Clone, Copy, Debug)]
+pub struct ExportOptions {
+    pub max_depth: usize,
+    pub min_visits: u64,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self { max_depth: 3, min_visits: 1 }
+    }
+}
+
+struct ExportedNode {
+    // Moves from the root, the shortest way of reaching a node shared by transpositions
+    path: Vec<usize>,
+    record: Record,
+    // Selection score as seen from the parent, none for the root
+    score: Option<f32>,
+    result: Option<GameResult>,
+    // Move and id of each exported child
+    children: Vec<(usize, usize)>,
+}
+
+pub fn write_dot<G: Game>(tree: &SearchTree<G>, options: ExportOptions, out: &mut impl io::Write) -> io::Result<()> {
+    writeln!(out, "digraph search_tree {{")?;
+    writeln!(out, "  node [shape=box, fontname=monospace];")?;
+    let nodes = collect(tree, options);
+    for (id, node) in nodes.iter().enumerate() {
+        let mut label = vec![path_label(&node.path), node.record.to_string()];
+        if let Some(score) = node.score {
+            label.push(format!("score {score:.3}"));
+        }
+        if let Some(result) = node.result {
+            label.push(format!("{result:?}"));
+        }
+        writeln!(out, "  n{id} [label=\"{}\"];", label.join("\\n"))?;
+    }
+    for (id, node) in nodes.iter().enumerate() {
+        for (m, child) in node.children.iter() {
+            writeln!(out, "  n{id} -> n{child} [label=\"{m}\"];")?;
+        }
+    }
+    writeln!(out, "}}")
+}
+
+pub fn write_json<G: Game>(tree: &SearchTree<G>, options: ExportOptions, out: &mut impl io::Write) -> io::Result<()> {
+    let nodes = collect(tree, options);
+    writeln!(out, "{{\"nodes\": [")?;
+    for (id, node) in nodes.iter().enumerate() {
+        let path: Vec<String> = node.path.iter().map(|m| m.to_string()).collect();
+        let children: Vec<String> = node
+            .children
+            .iter()
+            .map(|(m, child)| format!("{{\"move\": {m}, \"id\": {child}}}"))
+            .collect();
+        let score = match node.score {
+            Some(score) if score.is_finite() => score.to_string(),
+            _ => "null".to_string(),
+        };
+        let result = match node.result {
+            Some(result) => format!("\"{result:?}\""),
+            None => "null".to_string(),
+        };
+        let separator = if id + 1 < nodes.len() { "," } else { "" };
+        writeln!(
+            out,
+            "  {{\"id\": {id}, \"path\": [{}], \"wins\": {}, \"draws\": {}, \"played\": {}, \"score\": {score}, \"result\": {result}, \"children\": [{}]}}{separator}",
+            path.join(", "),
+            node.record.wins,
+            node.record.draws,
+            node.record.played,
+            children.join(", ")
+        )?;
+    }
+    writeln!(out, "]}}")
+}
+
+fn path_label(path: &[usize]) -> String {
+    match path.is_empty() {
+        true => "root".to_string(),
+        false => path.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" "),
+    }
+}
+
+// Breadth first so every node is labelled with its shortest path
+fn collect<G: Game>(tree: &SearchTree<G>, options: ExportOptions) -> Vec<ExportedNode> {
+    // Fixed so exports of the same tree can be diffed, only Thompson sampling scores are random
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut ids: HashMap<*const _, usize> = HashMap::new();
+    let mut nodes = vec![];
+    let mut pending: VecDeque<(ArcNode<G>, usize)> = VecDeque::new();
+
+    ids.insert(Arc::as_ptr(&tree.root), 0);
+    nodes.push(ExportedNode {
+        path: vec![],
+        record: tree.root.record.load(),
+        score: None,
+        result: tree.root.result.get().copied(),
+        children: vec![],
+    });
+    pending.push_back((tree.root.clone(), 0));
+
+    while let Some((node, id)) = pending.pop_front() {
+        if nodes[id].path.len() >= options.max_depth {
+            continue;
+        }
+        let children = match node.children.get() {
+            Some(children) => children,
+            None => continue,
+        };
+        let parent = node.record.load();
+        for (index, child) in children.iter().enumerate() {
+            let child = match child {
+                ValidMove::Valid(c) => c,
+                ValidMove::Invalid => continue,
+            };
+            let record = child.record.load();
+            if record.played < options.min_visits {
+                continue;
+            }
+            let child_id = match ids.get(&Arc::as_ptr(child)) {
+                Some(child_id) => *child_id,
+                None => {
+                    let candidate = Candidate {
+                        parent,
+                        child: record,
+                        prior: node.priors.get().map(|p| p[index]).unwrap_or_default(),
+                        amaf: node.amaf.get().map(|a| a[index].load()).unwrap_or_default(),
+                    };
+                    let mut path = nodes[id].path.clone();
+                    path.push(index);
+                    nodes.push(ExportedNode {
+                        path,
+                        record,
+                        score: Some(tree.selection_policy.score(&candidate, &mut rng)),
+                        result: child.result.get().copied(),
+                        children: vec![],
+                    });
+                    ids.insert(Arc::as_ptr(child), nodes.len() - 1);
+                    pending.push_back((child.clone(), nodes.len() - 1));
+                    nodes.len() - 1
+                }
+            };
+            nodes[id].children.push((index, child_id));
+        }
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        game::{board::Board, tic_tac_toe::TicTacToe},
+        mcst::{budget::SearchBudget, SearchTree},
+    };
+
+    use super::*;
+
+    fn export(tree: &SearchTree<Board>, options: ExportOptions, json: bool) -> String {
+        let mut out = vec![];
+        match json {
+            true => write_json(tree, options, &mut out).unwrap(),
+            false => write_dot(tree, options, &mut out).unwrap(),
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    pub fn dot_export_stops_at_max_depth() {
+        // Arrange
+        let mut tree = SearchTree::new(Board::default(), 2);
+        tree.search(SearchBudget::Iterations(50));
+
+        // Act
+        let dot = export(&tree, ExportOptions { max_depth: 1, min_visits: 0 }, false);
+
+        // Assert
+        assert!(dot.starts_with("digraph search_tree {"));
+        assert!(dot.contains("n0 [label=\"root\\n"));
+        assert_eq!(dot.matches(" -> ").count(), 7);
+        assert!(dot.contains("n0 -> n4 [label=\"3\"];"));
+    }
+
+    #[test]
+    pub fn json_export_skips_rarely_visited_nodes() {
+        // Arrange
+        let mut tree = SearchTree::new(Board::default(), 2);
+        tree.search(SearchBudget::Iterations(50));
+        let root_children = 7;
+
+        // Act
+        let all = export(&tree, ExportOptions { max_depth: 10, min_visits: 0 }, true);
+        let popular = export(&tree, ExportOptions { max_depth: 10, min_visits: 10 }, true);
+
+        // Assert
+        assert!(all.starts_with("{\"nodes\": ["));
+        assert!(all.trim_end().ends_with("]}"));
+        assert_eq!(all.matches("\"id\": 0, \"path\": []").count(), 1);
+        assert!(popular.matches("\"path\"").count() < all.matches("\"path\"").count());
+        assert!(all.matches("\"path\"").count() > root_children);
+    }
+
+    #[test]
+    pub fn shared_transpositions_are_exported_once() {
+        // Arrange
+        let mut tree = SearchTree::with_transpositions(TicTacToe::default(), 1);
+        tree.search(SearchBudget::Iterations(300));
+        let mut out = vec![];
+
+        // Act
+        write_json(&tree, ExportOptions { max_depth: 3, min_visits: 0 }, &mut out).unwrap();
+        let json = String::from_utf8(out).unwrap();
+
+        // Assert
+        // 504 move orders reach only 36 * 7 distinct positions after three moves
+        let depth_three = json
+            .lines()
+            .filter_map(|l| l.split("\"path\": [").nth(1))
+            .filter(|rest| rest[..rest.find(']').unwrap()].split(", ").count() == 3)
+            .count();
+        let nodes = json.matches("\"path\"").count();
+        let edges = json.matches("\"move\"").count();
+        assert!(depth_three > 0);
+        assert!(depth_three <= 252);
+        assert!(edges > nodes - 1);
+    }
+}
@@ -1,304 +1,567 @@
-use std::{
-    f32::consts::{E, SQRT_2},
-    sync::{Arc, RwLock},
-};
-
-use colored::Colorize;
-use log::debug;
-use rand::RngCore;
-
-use crate::{
-    game::board::{Board, WIDTH},
-    game::result::GameResult,
-};
-
-use self::{
-    node::{ActionLink, ArcNode, Node, NodeContent},
-    valid_move::ValidMove,
-};
-
-static EXPLORATION_CONSTANT: f32 = SQRT_2;
-
-pub mod node;
-mod playout;
-mod record;
-mod tests;
-mod valid_move;
-
-pub struct SearchTree {
-    pub root: ArcNode,
-    simulations: usize,
-}
-
-impl SearchTree {
-    pub fn new(board: Board, simulations: usize) -> Self {
-        Self {
-            root: Arc::new(NodeContent::new_root(board)),
-            simulations,
-        }
-    }
-
-    pub fn record_move(&mut self, index: usize, board: Board) -> Board {
-        let root = self.root.clone();
-        let children = match root.children.get() {
-            Some(children) => children,
-            None => {
-                debug!("Attempting to record move {index} but children haven't been init'd for node {:?}", self.root); // TODO: This shouldnt be happening as often as it is
-                self.expansion(root.clone());
-                root.children.get().unwrap()
-            }
-        };
-        let new_root = match &children[index] {
-            ValidMove::Valid(c) => c.clone(),
-
-            ValidMove::Invalid => {
-                panic!("Something went wrong - attempting to record an invalid move")
-            }
-        };
-
-        self.root = new_root.clone();
-        board
-    }
-
-    pub fn print_state(&self) {
-        println!("State winner: {:?}", self.root.result.get());
-        match self.root.children.get() {
-            Some(children) => {
-                for i in 0..WIDTH {
-                    match &children[i] {
-                        ValidMove::Valid(c) => {
-                            // Else rank moves by simulation count
-                            let wins = c.record.read().unwrap().wins as usize;
-                            let played = c.record.read().unwrap().played as usize;
-                            let result = c.result.get();
-
-                            println!("Option {i}: {wins}\\{played} - {result:?}",);
-                        }
-                        ValidMove::Invalid => println!("{i}: not valid"),
-                    };
-                }
-                println!("Expected move: {}", self.choose_move());
-            }
-            None => println!("Unexplored root"),
-        }
-    }
-
-    pub fn choose_move(&self) -> usize {
-        match self.root.children.get() {
-            Some(children) => {
-                let mut m: Option<usize> = None;
-                let mut m_s = i64::MIN;
-                for i in 0..WIDTH {
-                    let r = match &children[i] {
-                        ValidMove::Valid(c) => {
-                            // If move is a winner pick it
-                            let r = c.result.get();
-                            match r {
-                                Some(r) => match r {
-                                    GameResult::Win(winner) => {
-                                        if *winner == self.root.board().active_player {
-                                            return i;
-                                        } else {
-                                            -2
-                                        }
-                                    }
-                                    GameResult::Draw => 0,
-                                },
-                                None => c.record.read().unwrap().played as i64,
-                            }
-                        }
-                        ValidMove::Invalid => i64::MIN,
-                    };
-                    if r > m_s {
-                        m = Some(i);
-                        m_s = r
-                    }
-                }
-
-                if m.is_none() {
-                    self.root.board().print_board();
-                    panic!("no valid move found for node {:?}", self.root);
-                }
-                m.unwrap()
-            }
-            None => panic!("Attempting to choose move when root has no children"),
-        }
-    }
-
-    pub fn iterate(&mut self) {
-        // Game over no need to iterate
-        if self.root.result.get().is_some() {
-            return;
-        }
-
-        let selection = self.selection();
-
-        if selection.is_none() {
-            debug!("No expansion for root {:?}", self.root);
-            return;
-        }
-
-        self.expansion(selection.clone().unwrap());
-        match selection.clone().unwrap().children.get() {
-            Some(children) => {
-                for i in 0..WIDTH {
-                    match &children[i] {
-                        ValidMove::Valid(m) => {
-                            for i in 0..self.simulations {
-                                let sim_result = self.simulation(m.clone());
-                                backpropagation(m.clone(), sim_result);
-                            }
-                        }
-                        ValidMove::Invalid => (),
-                    }
-                }
-            }
-            None => (),
-        }
-    }
-
-    pub fn selection(&self) -> Option<ArcNode> {
-        let root = self.root.clone();
-        let root_sims = root.record.read().unwrap().played as f32;
-        let (selected, score) = traverse_tree_ucb(root.clone(), root_sims, 0);
-
-        // println!("Selected {selected:?} with score {score}");
-
-        if selected.is_none() {
-            debug!("No valid expansion for root {root:?}");
-            return None;
-        }
-
-        if !selected.clone().unwrap().is_leaf() {
-            match root.children.get() {
-                Some(children) => {
-                    println!("{children:?}");
-                }
-                None => (),
-            }
-            root.board().print_board();
-            println!(
-                "{} {} : {:?}",
-                root.board.yellow_bb.to_string().yellow(),
-                root.board.blue_bb.to_string().blue(),
-                root.board.column_pieces
-            );
-            panic!("Selected is not leaf! {selected:?} score {score}");
-        }
-        selected
-    }
-
-    pub fn expansion(&mut self, mut leaf: ArcNode) {
-        let moves = leaf.board().get_moves();
-
-        let mut new_leaves: [ActionLink; WIDTH] = std::array::from_fn(|_| ValidMove::Invalid);
-
-        for selected_move in 0..WIDTH {
-            if moves.contains(&selected_move) {
-                let new_state = leaf.board().play_move(selected_move);
-                let new_arc_node = leaf.new_child(selected_move, new_state);
-                new_leaves[selected_move] = ValidMove::Valid(new_arc_node.clone());
-
-                match new_arc_node.result.get() {
-                    Some(r) => {
-                        match r {
-                            GameResult::Win(winner) => {
-                                if *winner == leaf.board().active_player {
-                                    match leaf.result.set(*r) {
-                                        Err(val) => {
-                                            if Some(&val) != leaf.result.get() {
-                                                panic!("Could not write result to {:?}. Write Value: {val}", leaf)
-                                            }
-                                        }
-                                        _ => (),
-                                    }
-                                }
-                            }
-                            GameResult::Draw => (), // TODO: Maybe we need to backpropagate draws?
-                        }
-                    }
-                    None => (),
-                }
-            }
-        }
-        leaf.children.set(new_leaves);
-    }
-
-    pub fn simulation(&mut self, leaf: ArcNode) -> GameResult {
-        let board = leaf.clone().board();
-        playout::from(board).fair_random_result()
-    }
-}
-
-pub fn backpropagation(mut leaf: ArcNode, result: GameResult) {
-    leaf.record_result(result);
-    match leaf.parent.upgrade() {
-        Some(l) => backpropagation(l, result),
-        None => {}
-    }
-}
-
-fn traverse_tree_ucb(node: ArcNode, parent_sims: f32, depth: usize) -> (Option<ArcNode>, f32) {
-    if node.clone().board().winner.is_some() {
-        (None, f32::MIN)
-    } else if node.clone().is_leaf() {
-        (
-            Some(node.clone()),
-            calculate_node_uctb(node.clone(), parent_sims),
-        )
-    } else {
-        match node.children.get() {
-            Some(children) => {
-                let sims = node.record.read().unwrap().played as f32;
-                let mut max_score = f32::MIN;
-                let mut selected_node: Option<ArcNode> = None;
-                for i in 0..WIDTH {
-                    if let ValidMove::Valid(child) = &children[i] {
-                        let (selected, r) = traverse_tree_ucb(child.clone(), sims, depth + 1);
-
-                        if r > max_score {
-                            max_score = r;
-                            selected_node = selected.clone();
-                        }
-                    }
-                }
-
-                // return max of
-                (selected_node, max_score)
-            }
-            None => panic!("Trying to find children of childless node {:?}", node),
-        }
-    }
-}
-
-fn calculate_node_uctb(node: ArcNode, parent_sims: f32) -> f32 {
-    match node.clone().record.try_read() {
-        Ok(r) => {
-            let mean = r.wins as f32 / r.played as f32;
-            let exploration_bias =
-                EXPLORATION_CONSTANT * f32::sqrt(f32::ln(parent_sims) / r.played as f32);
-            mean + exploration_bias
-        }
-        Err(e) => panic!("Calculate node uctb error: {e}"),
-    }
-}
-
-#[cfg(test)]
-mod test {
-    use crate::{
-        game::board::Board,
-        mcst::{
-            node::{ArcNode, NodeContent},
-            SearchTree,
-        },
-    };
-
-    #[test]
-    pub fn insert_to_tree_root() {
-        // Act
-        let tree = SearchTree::new(Board::default(), 10);
-
-        // Assert
-        assert_eq!(tree.root.board, Board::default());
-    }
-}
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+    thread,
+    time::Instant,
+};
+
+use log::debug;
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+
+use crate::game::{player::Player, result::GameResult, Game};
+
+use self::{
+    budget::{SearchBudget, SearchProgress},
+    error::SearchError,
+    node::{ActionLink, ArcNode, Node, NodeContent},
+    playout::{PlayoutPolicy, UniformRandom},
+    report::SearchReport,
+    record::{AtomicRecord, Record},
+    selection::{Candidate, SelectionPolicy, Ucb1},
+    valid_move::ValidMove,
+};
+
+pub mod budget;
+pub mod error;
+pub mod export;
+pub mod node;
+pub mod playout;
+pub mod record;
+pub mod report;
+pub mod selection;
+#[cfg(test)]
+mod tests;
+pub mod valid_move;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MoveStats {
+    pub index: usize,
+    pub record: Record,
+    pub result: Option<GameResult>,
+}
+
+type TranspositionTable<G> = Mutex<HashMap<<G as Game>::Key, Weak<NodeContent<G>>>>;
+
+pub struct SearchTree<G: Game> {
+    pub root: ArcNode<G>,
+    // Stop searching once the most visited move can no longer be overtaken within the budget. Off unless
+    // turned on, so an iteration budget runs every iteration
+    pub early_stop: bool,
+    // UCB1 with an exploration constant of sqrt(2) unless replaced
+    pub selection_policy: Box<dyn SelectionPolicy>,
+    // Uniformly random playouts unless replaced
+    pub playout_policy: Box<dyn PlayoutPolicy<G>>,
+    // Seeds every search, seeded from entropy unless replaced
+    pub rng: StdRng,
+    simulations: usize,
+    nodes: AtomicUsize,
+    playouts: AtomicUsize,
+    iterations: AtomicUsize,
+    last_search: SearchProgress,
+    transpositions: Option<TranspositionTable<G>>,
+}
+
+impl<G: Game> SearchTree<G> {
+    pub fn new(board: G, simulations: usize) -> Self {
+        Self {
+            root: Arc::new(NodeContent::new_root(board)),
+            early_stop: false,
+            selection_policy: Box::new(Ucb1::default()),
+            playout_policy: Box::new(UniformRandom),
+            rng: StdRng::from_entropy(),
+            simulations,
+            nodes: AtomicUsize::new(0),
+            playouts: AtomicUsize::new(0),
+            iterations: AtomicUsize::new(0),
+            last_search: SearchProgress::default(),
+            transpositions: None,
+        }
+    }
+
+    // Positions reached by different move orders share a single node, turning the tree into a DAG.
+    // Only sound for games where a position can never repeat.
+    pub fn with_transpositions(board: G, simulations: usize) -> Self {
+        let tree = Self {
+            transpositions: Some(Mutex::new(HashMap::new())),
+            ..Self::new(board, simulations)
+        };
+        tree.transpose(&tree.root);
+        tree
+    }
+
+    // Returns the node already in the table for this position, or registers the given one
+    fn transpose(&self, node: &ArcNode<G>) -> Option<ArcNode<G>> {
+        let mut table = self.transpositions.as_ref()?.lock().unwrap();
+        let key = node.board().key();
+        match table.get(&key).and_then(|n| n.upgrade()) {
+            Some(existing) => Some(existing),
+            None => {
+                table.insert(key, Arc::downgrade(node));
+                None
+            }
+        }
+    }
+
+    // Nodes created and playouts run over the lifetime of the tree
+    pub fn nodes(&self) -> usize {
+        self.nodes.load(Ordering::Relaxed)
+    }
+
+    pub fn playouts(&self) -> usize {
+        self.playouts.load(Ordering::Relaxed)
+    }
+
+    // Iterates until the budget runs out or the root is proven
+    pub fn search(&mut self, budget: SearchBudget) -> SearchProgress {
+        let start = self.progress(Instant::now(), &SearchProgress::default());
+        let mut rng = StdRng::seed_from_u64(self.rng.gen());
+        self.last_search = self.search_worker(budget, Instant::now(), &start, &mut rng);
+        self.last_search
+    }
+
+    // Tree parallel search: every thread selects, expands and backpropagates on the shared tree,
+    // using virtual losses to spread out over different leaves
+    pub fn search_parallel(&mut self, budget: SearchBudget, threads: usize) -> SearchProgress
+    where
+        G: Send + Sync,
+        G::Key: Send,
+    {
+        let started = Instant::now();
+        let start = self.progress(started, &SearchProgress::default());
+        let rngs: Vec<StdRng> = (0..threads.max(1)).map(|_| StdRng::seed_from_u64(self.rng.gen())).collect();
+        let tree = &*self;
+        thread::scope(|scope| {
+            for mut rng in rngs {
+                scope.spawn(move || tree.search_worker(budget, started, &start, &mut rng));
+            }
+        });
+        self.last_search = self.progress(started, &start);
+        self.last_search
+    }
+
+    fn search_worker(
+        &self,
+        budget: SearchBudget,
+        started: Instant,
+        start: &SearchProgress,
+        rng: &mut StdRng,
+    ) -> SearchProgress {
+        loop {
+            let progress = self.progress(started, start);
+            if budget.exhausted(&progress) || self.decided(budget.remaining_playouts(&progress)) {
+                return progress;
+            }
+            if !self.run_iteration(rng) {
+                return self.progress(started, start);
+            }
+        }
+    }
+
+    fn progress(&self, started: Instant, start: &SearchProgress) -> SearchProgress {
+        SearchProgress {
+            iterations: self.iterations.load(Ordering::Relaxed) - start.iterations,
+            nodes: self.nodes() - start.nodes,
+            playouts: self.playouts() - start.playouts,
+            elapsed: started.elapsed(),
+        }
+    }
+
+    fn decided(&self, remaining_playouts: f64) -> bool {
+        if self.root.result.get().is_some() {
+            return true;
+        }
+        if !self.early_stop {
+            return false;
+        }
+        let children = match self.root.children.get() {
+            Some(children) => children,
+            None => return false,
+        };
+        let mut visits: Vec<u64> = children
+            .iter()
+            .filter_map(|child| match child {
+                ValidMove::Valid(c) if c.result.get().is_none() => Some(c.record.load().played),
+                _ => None,
+            })
+            .collect();
+        visits.sort_unstable_by(|a, b| b.cmp(a));
+        match visits.as_slice() {
+            [] | [_] => false,
+            [first, second, ..] => (first - second) as f64 > remaining_playouts,
+        }
+    }
+
+    pub fn record_move(&mut self, index: usize, board: G) -> G {
+        match self.try_record_move(index, board) {
+            Ok(board) => board,
+            Err(e) => panic!("Something went wrong - attempting to record move {index}: {e}"),
+        }
+    }
+
+    pub fn try_record_move(&mut self, index: usize, board: G) -> Result<G, SearchError> {
+        let root = self.root.clone();
+        let children = match root.children.get() {
+            Some(children) => children,
+            None => {
+                debug!("Attempting to record move {index} but children haven't been init'd for node {:?}", self.root); // TODO: This shouldnt be happening as often as it is
+                self.expansion(root.clone());
+                root.children.get().unwrap()
+            }
+        };
+        let new_root = match children.get(index) {
+            Some(ValidMove::Valid(c)) => c.clone(),
+            _ => return Err(SearchError::InvalidMove(index)),
+        };
+
+        self.root = new_root.clone();
+        if let Some(table) = &self.transpositions {
+            table.lock().unwrap().retain(|_, node| node.strong_count() > 0);
+        }
+        Ok(board)
+    }
+
+    // Starts a fresh tree from a position that isn't below the root, such as after a take-back
+    pub fn reset_root(&mut self, board: G) {
+        self.root = Arc::new(NodeContent::new_root(board));
+        if let Some(table) = &self.transpositions {
+            table.lock().unwrap().clear();
+            self.transpose(&self.root);
+        }
+    }
+
+    pub fn print_state(&self) {
+        println!("{}", self.report());
+    }
+
+    pub fn report(&self) -> SearchReport {
+        let (tree_size, depth) = report::measure(&self.root);
+        let moves = self.root_moves();
+        SearchReport {
+            best_move: self.try_choose_move().ok(),
+            principal_variation: report::principal_variation(&self.root),
+            moves,
+            root_result: self.root.result.get().copied(),
+            tree_size,
+            depth,
+            last_search: self.last_search,
+        }
+    }
+
+    // Statistics for each legal move from the root, empty until the root is expanded
+    pub fn root_moves(&self) -> Vec<MoveStats> {
+        match self.root.children.get() {
+            Some(children) => children
+                .iter()
+                .enumerate()
+                .filter_map(|(index, child)| match child {
+                    ValidMove::Valid(c) => Some(MoveStats {
+                        index,
+                        record: c.record.load(),
+                        result: c.result.get().copied(),
+                    }),
+                    ValidMove::Invalid => None,
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    pub fn choose_move(&self) -> usize {
+        match self.try_choose_move() {
+            Ok(m) => m,
+            Err(e) => panic!("Attempting to choose move for node {:?}: {e}", self.root),
+        }
+    }
+
+    pub fn try_choose_move(&self) -> Result<usize, SearchError> {
+        match self.root.children.get() {
+            Some(_) => choose_from(&self.root_moves(), self.root.board().side_to_move()),
+            None if self.root.board().legal_moves().is_empty() => Err(SearchError::NoLegalMoves),
+            None => Err(SearchError::Unexpanded),
+        }
+    }
+
+    // Returns false when there was nothing left to search
+    pub fn iterate(&mut self) -> bool {
+        let mut rng = StdRng::seed_from_u64(self.rng.gen());
+        self.run_iteration(&mut rng)
+    }
+
+    fn run_iteration(&self, rng: &mut StdRng) -> bool {
+        // Game over no need to iterate
+        if self.root.result.get().is_some() {
+            return false;
+        }
+
+        let mut path = match self.selection(rng) {
+            Some(path) => path,
+            None => {
+                debug!("No expansion for root {:?}", self.root);
+                return false;
+            }
+        };
+        let selection = path.last().unwrap().clone();
+        path.iter().for_each(|n| n.record.add_virtual_loss());
+
+        self.expansion(selection.clone());
+        let rave = self.selection_policy.uses_amaf();
+        let mut moves = if rave { path_actions(&path) } else { vec![] };
+        if let Some(children) = selection.children.get() {
+            for (index, child) in children.iter().enumerate() {
+                if let ValidMove::Valid(m) = child {
+                    path.push(m.clone());
+                    moves.push(index);
+                    let tree_moves = moves.len();
+                    for _ in 0..self.simulations {
+                        let sim_result = self.simulation(m.clone(), &mut moves, rng);
+                        backpropagation(&path, sim_result);
+                        if rave {
+                            path.iter().enumerate().for_each(|(i, n)| n.record_amaf(&moves[i..], sim_result));
+                        }
+                        moves.truncate(tree_moves);
+                    }
+                    moves.pop();
+                    path.pop();
+                }
+            }
+        }
+
+        path.iter().for_each(|n| n.record.remove_virtual_loss());
+        self.iterations.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    // Path from the root to the selected leaf
+    pub fn selection(&self, rng: &mut dyn RngCore) -> Option<Vec<ArcNode<G>>> {
+        let root = self.root.clone();
+        if root.is_leaf() && root.result.get().is_none() {
+            return Some(vec![root]);
+        }
+        let mut best = (f32::MIN, None);
+        let mut search = Traversal {
+            policy: self.selection_policy.as_ref(),
+            rng,
+            path: vec![],
+            best: &mut best,
+        };
+        // The root has no parent, its own record stands in
+        search.visit(root.clone(), root.record.load(), 1.0, Record::default());
+
+        if best.1.is_none() {
+            debug!("No valid expansion for root {root:?}");
+        }
+        // Another search thread may have expanded the leaf since, in which case expansion is a no-op
+        best.1
+    }
+
+    pub fn expansion(&self, leaf: ArcNode<G>) {
+        if !leaf.is_leaf() {
+            return;
+        }
+        let moves = leaf.board().legal_moves();
+        let _ = leaf.priors.set(leaf.board().priors().into_boxed_slice());
+        if self.selection_policy.uses_amaf() {
+            let _ = leaf.amaf.set((0..G::ACTIONS).map(|_| AtomicRecord::default()).collect());
+        }
+
+        let mut new_leaves: Vec<ActionLink<G>> = (0..G::ACTIONS).map(|_| ValidMove::Invalid).collect();
+        let mut created = vec![];
+
+        for selected_move in moves {
+            let new_state = leaf.board().apply_move(selected_move);
+            let new_arc_node = leaf.new_child(selected_move, new_state);
+            let child = match self.transpose(&new_arc_node) {
+                Some(existing) => {
+                    existing.add_parent(Arc::downgrade(&leaf));
+                    existing
+                }
+                None => {
+                    created.push(new_arc_node.clone());
+                    new_arc_node
+                }
+            };
+            new_leaves[selected_move] = ValidMove::Valid(child);
+        }
+        if leaf.children.set(new_leaves.into_boxed_slice()).is_ok() {
+            self.nodes.fetch_add(created.len(), Ordering::Relaxed);
+        }
+        propagate_proof(leaf);
+    }
+
+    // Appends the moves played out to `moves`
+    pub fn simulation(&self, leaf: ArcNode<G>, moves: &mut Vec<usize>, rng: &mut dyn RngCore) -> GameResult {
+        self.playouts.fetch_add(1, Ordering::Relaxed);
+        let board = leaf.board();
+        playout::from(board, self.playout_policy.as_ref(), rng, moves)
+    }
+}
+
+// A proven win if there is one, otherwise the most played move. A certain draw beats any move expected to
+// score less than half
+pub fn choose_from(moves: &[MoveStats], player: Player) -> Result<usize, SearchError> {
+    let mut best: Option<(usize, (i64, f32))> = None;
+    let mut draw: Option<usize> = None;
+    for m in moves {
+        let rank = match m.result {
+            Some(GameResult::Win(winner)) if winner == player => return Ok(m.index),
+            Some(GameResult::Win(_)) => (-2, 0.0),
+            Some(GameResult::Draw) => {
+                draw = draw.or(Some(m.index));
+                continue;
+            }
+            None if m.record.played == 0 => (0, 0.0),
+            None => (m.record.played as i64, m.record.mean()),
+        };
+        if !matches!(best, Some((_, b)) if rank.0.cmp(&b.0).then(rank.1.total_cmp(&b.1)).is_le()) {
+            best = Some((m.index, rank));
+        }
+    }
+
+    match (best, draw) {
+        (Some((_, rank)), Some(draw)) if rank.0 < 0 || rank.1 < 0.5 => Ok(draw),
+        (Some((m, _)), _) => Ok(m),
+        (None, Some(draw)) => Ok(draw),
+        (None, None) => Err(SearchError::NoLegalMoves),
+    }
+}
+
+// Results are recorded along the path that was searched, so a node shared between transpositions
+// gathers every playout through it while each parent only counts its own
+pub fn backpropagation<G: Game>(path: &[ArcNode<G>], result: GameResult) {
+    for node in path.iter().rev() {
+        node.record_result(result);
+    }
+}
+
+// The action taken at each step of a path through the tree
+fn path_actions<G: Game>(path: &[ArcNode<G>]) -> Vec<usize> {
+    path.windows(2)
+        .map(|pair| {
+            let children = pair[0].children.get().expect("Path runs through an unexpanded node");
+            children
+                .iter()
+                .position(|c| matches!(c, ValidMove::Valid(c) if Arc::ptr_eq(c, &pair[1])))
+                .expect("Path steps to a node that isn't a child")
+        })
+        .collect()
+}
+
+// MCTS-Solver: a node is won if any move wins for the player to move, lost if every move loses
+// and drawn if every move is proven and the best of them is a draw
+fn prove<G: Game>(node: &ArcNode<G>) -> Option<GameResult> {
+    if let Some(r) = node.result.get() {
+        return Some(*r);
+    }
+    let children = node.children.get()?;
+    let mover = node.board().side_to_move();
+
+    let mut draw = false;
+    let mut loss = None;
+    let mut unproven = false;
+    for child in children.iter() {
+        if let ValidMove::Valid(c) = child {
+            match c.result.get() {
+                Some(GameResult::Win(winner)) if *winner == mover => {
+                    let _ = node.result.set(GameResult::Win(mover));
+                    return node.result.get().copied();
+                }
+                Some(r @ GameResult::Win(_)) => loss = Some(*r),
+                Some(GameResult::Draw) => draw = true,
+                None => unproven = true,
+            }
+        }
+    }
+
+    if unproven {
+        return None;
+    } else if draw {
+        let _ = node.result.set(GameResult::Draw);
+    } else if let Some(loss) = loss {
+        let _ = node.result.set(loss);
+    }
+    node.result.get().copied()
+}
+
+// Walk up from a newly expanded node, through every parent, for as long as nodes become proven
+fn propagate_proof<G: Game>(node: ArcNode<G>) {
+    let mut pending = vec![node];
+    while let Some(node) = pending.pop() {
+        if prove(&node).is_some() {
+            pending.extend(node.live_parents());
+        }
+    }
+}
+
+struct Traversal<'a, G: Game> {
+    policy: &'a dyn SelectionPolicy,
+    rng: &'a mut dyn RngCore,
+    path: Vec<ArcNode<G>>,
+    best: &'a mut (f32, Option<Vec<ArcNode<G>>>),
+}
+
+impl<G: Game> Traversal<'_, G> {
+    // Finds the leaf with the highest score anywhere below node, keeping the path that leads to it
+    fn visit(&mut self, node: ArcNode<G>, parent: Record, prior: f32, amaf: Record) {
+        // Proven subtrees have nothing left to learn
+        if node.result.get().is_some() {
+            return;
+        }
+        self.path.push(node.clone());
+        let record = node.record.load();
+        if node.is_leaf() {
+            let candidate = Candidate { parent, child: record, prior, amaf };
+            let score = self.policy.score(&candidate, self.rng);
+            if score > self.best.0 {
+                *self.best = (score, Some(self.path.clone()));
+            }
+        } else {
+            match (node.children.get(), node.priors.get()) {
+                (Some(children), Some(priors)) => {
+                    for (index, (child, prior)) in children.iter().zip(priors.iter()).enumerate() {
+                        if let ValidMove::Valid(child) = child {
+                            let amaf = node.amaf.get().map(|amaf| amaf[index].load()).unwrap_or_default();
+                            self.visit(child.clone(), record, *prior, amaf);
+                        }
+                    }
+                }
+                _ => panic!("Trying to find children of childless node {:?}", node),
+            }
+        }
+        self.path.pop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        game::{board::Board, tic_tac_toe::TicTacToe},
+        mcst::SearchTree,
+    };
+
+    #[test]
+    pub fn insert_to_tree_root() {
+        // Act
+        let tree = SearchTree::new(Board::default(), 10);
+
+        // Assert
+        assert_eq!(tree.root.board, Board::default());
+    }
+
+    #[test]
+    pub fn search_generic_game_finds_winning_move() {
+        // Arrange
+        // X X _ / O O _ / _ _ _
+        let mut tree = SearchTree::new(TicTacToe::setup(0x3, 0x18), 5);
+
+        // Act
+        for _ in 0..10 {
+            tree.iterate();
+        }
+
+        // Assert
+        assert_eq!(tree.choose_move(), 2);
+    }
+}
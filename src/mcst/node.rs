@@ -1,234 +1,180 @@
-use core::fmt::Debug;
-use std::{
-    cell::OnceCell,
-    iter::Once,
-    sync::{Arc, Mutex, RwLock, Weak},
-};
-
-use crate::game::{
-    board::{Board, WIDTH},
-    player::Player,
-    result::GameResult,
-};
-
-use super::{record::Record, valid_move::ValidMove};
-
-pub struct NodeContent {
-    pub board: Board,
-    pub parent: Weak<Self>,
-    pub record: RwLock<Record>,
-    pub children: OnceCell<[ValidMove<ArcNode>; 7]>,
-    pub result: OnceCell<GameResult>,
-}
-
-impl NodeContent {
-    pub(super) fn new_root(board: Board) -> Self {
-        NodeContent {
-            board: board,
-            parent: Weak::new(),
-            record: Default::default(),
-            children: OnceCell::new(),
-            result: OnceCell::new(),
-        }
-    }
-    pub(super) fn new_child(parent_ptr: Weak<Self>, board: Board) -> Self {
-        let result = OnceCell::new();
-        if let Some(winner) = board.winner {
-            let _ = result.set(match winner {
-                Player::Yellow | Player::Blue => GameResult::Win(winner),
-                Player::NoPlayer => GameResult::Draw,
-            });
-        }
-        NodeContent {
-            board: board,
-            parent: parent_ptr,
-            record: Default::default(),
-            children: OnceCell::new(),
-            result,
-        }
-    }
-
-    pub fn is_leaf(&self) -> bool {
-        self.children.get().is_none()
-    }
-}
-
-impl Debug for NodeContent {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // let win = match self.result.try_read() {
-        //     Ok(r) => {
-        //         if r.is_some() {
-        //             let o = r.unwrap();
-        //             format!(
-        //                 "Winner: {o}"
-        //             )
-        //         } else {
-        //             "Incomplete".to_string()
-        //         }
-        //     }
-
-        //     Err(e) => panic!("{e}"),
-        // };
-        f.debug_tuple("Node")
-            .field(&self.board)
-            .field(&self.record.read())
-            .field(&self.is_leaf())
-            .field(&self.result.get())
-            .finish()
-    }
-}
-
-pub type ArcNode = Arc<NodeContent>;
-pub type ActionLink = ValidMove<ArcNode>;
-
-// pub struct Tree {
-//     root: Link,
-// }
-
-// impl Tree {
-//     pub fn new(root: Link) -> Self {
-//         Self { root }
-//     }
-// }
-
-pub trait Node {
-    fn board(&self) -> Board;
-    fn record_result(&mut self, result: GameResult);
-    fn new_child(&self, index: usize, board: Board) -> Self;
-    fn seek(self, board: Board) -> Option<Self>
-    where
-        Self: Sized;
-}
-
-impl Node for ArcNode {
-    fn board(&self) -> Board {
-        self.board
-    }
-
-    fn record_result(&mut self, result: GameResult) {
-        let win = match result {
-            GameResult::Win(winner) => winner == self.board().active_player,
-            GameResult::Draw => panic!("attempting to record a draw"),
-        };
-        match self.record.try_write() {
-            Ok(mut r) => r.increment(win),
-            Err(e) => panic!("Record result lock error {e:?}"),
-        }
-    }
-
-    fn new_child(&self, index: usize, board: Board) -> Self {
-        let parent_ptr = Arc::downgrade(self);
-        Arc::new(NodeContent::new_child(parent_ptr, board))
-    }
-
-    fn seek(self, board: Board) -> Option<Self> {
-        if self.board == board {
-            return Some(self.clone());
-        } else {
-            match self.children.get() {
-                Some(children) => {
-                    for i in 0..WIDTH {
-                        let r = match &children[i] {
-                            ValidMove::Valid(s) => {
-                                match s.clone().seek(board) {
-                                    Some(tn) => return Some(tn),
-                                    None => continue,
-                                }
-                            }
-                            ValidMove::Invalid => None,
-                        };
-                        if r.is_some() {
-                            return r
-                        }
-                    };
-                    None
-                }
-                None => None,
-            }
-        }
-    }
-
-    // fn new(board: Board, parent: Option<Weak<Self>>) -> Self { // , parent: Option<Rc<Node>>
-    //     Self {
-    //         board,
-    //         parent,
-    //         children: Default::default(),
-    //     }
-    // }
-
-    // pub fn insert(&mut self, index: usize, board: Board, r: Rc<RefCell<Self>>) {
-    //     if self.children[index].is_some() {
-    //         panic!("Attempting to insert over existing node");
-    //     }
-    //     let parent_ptr = Rc::downgrade(&r);
-    //     let new_node = Rc::new(RefCell::new(Node::new(board, Some(parent_ptr))));
-
-    //     self.children[index] = Some(new_node);
-    // }
-}
-
-#[cfg(test)]
-mod test {
-    use std::borrow::BorrowMut;
-
-    use super::*;
-
-    #[test]
-    pub fn find_child_on_root_returns_root() {
-        // Arrange
-        let root = Some(ArcNode::new(NodeContent::new_root(Board::default())));
-
-        // Act
-        let seek = root.unwrap().seek(Board::default());
-
-        // Assert
-        assert!(seek.is_some());
-    }
-
-    // #[test]
-    // pub fn find_child_not_root() {
-    //     // Arrange
-    //     let board1 = Board::setup(1, 0, [0; WIDTH]);
-    //     let board2 = Board::setup(2, 0, [0; WIDTH]);
-    //     let root = Some(ArcNode::new(NodeContent::new_root(Board::default())));
-
-    //     insert_to_node_index(&mut root.clone().unwrap().clone(), 0, board1);
-    //     insert_to_node_index(&mut root.clone().unwrap().clone(), 1, board2);
-
-    //     // Act
-    //     let seek = root.unwrap().seek(board2);
-
-    //     // Assert
-    //     assert!(seek.is_some());
-    //     assert_eq!(seek.as_ref().unwrap().board, board2)
-    // }
-
-    // #[test]
-    // pub fn is_leaf_all_children_assigned() {
-    //     // Arrange
-    //     let board = Board::default();
-    //     let root = Some(ArcNode::new(NodeContent::new_root(board)));
-
-    //     for i in 0..WIDTH {
-    //         insert_to_node_index(&mut root.clone().unwrap().clone(), i, board.play_move(i));
-    //     }
-    //     // Assert
-    //     assert!(!root.unwrap().is_leaf())
-    // }
-
-    // #[test]
-    // pub fn is_leaf_no_valid_moves_without_children_assigned() {
-    //     // Arrange
-    //     let board = Board::setup(558380617816, 4362610851, [2, 1, 0, 1, 6, 5, 4]);
-    //     let root = Some(ArcNode::new(NodeContent::new_root(board)));
-
-    //     for i in 0..WIDTH {
-    //         if i == 4 {
-    //             continue;
-    //         }
-    //         insert_to_node_index(&mut root.clone().unwrap().clone(), i, board.play_move(i));
-    //     }
-    //     // Assert
-    //     assert!(!root.unwrap().is_leaf())
-    // }
-}
+use core::fmt::Debug;
+use std::sync::{Arc, OnceLock, RwLock, Weak};
+
+use crate::game::{result::GameResult, Game};
+
+use super::{record::AtomicRecord, valid_move::ValidMove};
+
+pub struct NodeContent<G: Game> {
+    pub board: G,
+    // More than one parent only when transpositions are shared
+    pub parents: RwLock<Vec<Weak<Self>>>,
+    pub record: AtomicRecord,
+    pub children: OnceLock<Box<[ActionLink<G>]>>,
+    // Prior for each action, filled in on expansion
+    pub priors: OnceLock<Box<[f32]>>,
+    // All-moves-as-first statistics for each action, from the perspective of the side to move.
+    // Only filled in on expansion when the selection policy makes use of them
+    pub amaf: OnceLock<Box<[AtomicRecord]>>,
+    pub result: OnceLock<GameResult>,
+}
+
+impl<G: Game> NodeContent<G> {
+    pub(super) fn new_root(board: G) -> Self {
+        NodeContent {
+            board,
+            parents: RwLock::new(vec![]),
+            record: Default::default(),
+            children: OnceLock::new(),
+            priors: OnceLock::new(),
+            amaf: OnceLock::new(),
+            result: OnceLock::new(),
+        }
+    }
+    pub(super) fn new_child(parent_ptr: Weak<Self>, board: G) -> Self {
+        let result = OnceLock::new();
+        if let Some(r) = board.result() {
+            let _ = result.set(r);
+        }
+        NodeContent {
+            board,
+            parents: RwLock::new(vec![parent_ptr]),
+            record: Default::default(),
+            children: OnceLock::new(),
+            priors: OnceLock::new(),
+            amaf: OnceLock::new(),
+            result,
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.children.get().is_none()
+    }
+
+    pub fn add_parent(&self, parent: Weak<Self>) {
+        self.parents.write().unwrap().push(parent);
+    }
+
+    pub fn live_parents(&self) -> Vec<Arc<Self>> {
+        self.parents.read().unwrap().iter().filter_map(|p| p.upgrade()).collect()
+    }
+
+    // `moves` starts with the move made from this node, every other move after it is ours.
+    // Each action only counts the first time we play it
+    pub fn record_amaf(&self, moves: &[usize], result: GameResult) {
+        let amaf = match self.amaf.get() {
+            Some(amaf) => amaf,
+            None => return,
+        };
+        let mover = self.board.side_to_move();
+        let mut seen = vec![false; amaf.len()];
+        for m in moves.iter().step_by(2) {
+            if seen[*m] {
+                continue;
+            }
+            seen[*m] = true;
+            match result {
+                GameResult::Win(winner) => amaf[*m].increment(winner == mover),
+                GameResult::Draw => amaf[*m].increment_draw(),
+            }
+        }
+    }
+}
+
+impl<G: Game> Debug for NodeContent<G> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Node")
+            .field(&self.board)
+            .field(&self.record.load())
+            .field(&self.is_leaf())
+            .field(&self.result.get())
+            .finish()
+    }
+}
+
+pub type ArcNode<G> = Arc<NodeContent<G>>;
+pub type ActionLink<G> = ValidMove<ArcNode<G>>;
+
+pub trait Node<G: Game> {
+    fn board(&self) -> G;
+    fn record_result(&self, result: GameResult);
+    fn new_child(&self, index: usize, board: G) -> Self;
+    fn seek(self, board: G) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+impl<G: Game> Node<G> for ArcNode<G> {
+    fn board(&self) -> G {
+        self.board
+    }
+
+    // Records are kept from the perspective of the player who moved into this node
+    fn record_result(&self, result: GameResult) {
+        match result {
+            GameResult::Win(winner) => self.record.increment(winner != self.board().side_to_move()),
+            GameResult::Draw => self.record.increment_draw(),
+        }
+    }
+
+    fn new_child(&self, _index: usize, board: G) -> Self {
+        let parent_ptr = Arc::downgrade(self);
+        Arc::new(NodeContent::new_child(parent_ptr, board))
+    }
+
+    fn seek(self, board: G) -> Option<Self> {
+        if self.board == board {
+            return Some(self.clone());
+        }
+        match self.children.get() {
+            Some(children) => {
+                for child in children.iter() {
+                    if let ValidMove::Valid(s) = child {
+                        if let Some(tn) = s.clone().seek(board) {
+                            return Some(tn);
+                        }
+                    }
+                }
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::{board::Board, player::Player};
+
+    use super::*;
+
+    #[test]
+    pub fn amaf_counts_only_our_first_play_of_each_action() {
+        // Arrange
+        let root = ArcNode::new(NodeContent::new_root(Board::default()));
+        let _ = root.amaf.set((0..7).map(|_| AtomicRecord::default()).collect());
+
+        // Act
+        // Yellow plays 3, 3 again and 4, blue's 2 is ignored
+        root.record_amaf(&[3, 2, 3, 2, 4], GameResult::Win(Player::Yellow));
+
+        // Assert
+        let amaf = root.amaf.get().unwrap();
+        assert_eq!(amaf[3].load().played, 1);
+        assert_eq!(amaf[3].load().wins, 1);
+        assert_eq!(amaf[4].load().wins, 1);
+        assert_eq!(amaf[2].load().played, 0);
+    }
+
+    #[test]
+    pub fn find_child_on_root_returns_root() {
+        // Arrange
+        let root = ArcNode::new(NodeContent::new_root(Board::default()));
+
+        // Act
+        let seek = root.seek(Board::default());
+
+        // Assert
+        assert!(seek.is_some());
+    }
+}
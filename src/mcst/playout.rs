@@ -1,31 +1,279 @@
-use std::fmt::Write;
-
-use crate::game::{board::Board, result::GameResult};
-use rand::RngCore;
-
-pub fn from(mut board: Board) -> GameResult {
-    if let Some(r) = board.winner {
-        return GameResult::Win(r)
-    }
-    if board.get_moves().len() == 0 {
-        println!("Trying to simulate state with no moves: {board:?}");
-    }
-    let mut rand = rand::thread_rng();
-    for i in 0..1000 {
-        let moves = board.get_moves();
-        if board.get_moves().len() == 0 {
-            println!("depth {i} board: {board:?}");
-            board.print_board();
-        }
-
-
-        let rand_index: usize = rand.next_u64() as usize % moves.len();
-        board = board.play_move(moves[rand_index]);
-
-        match board.winner {
-            Some(result) => return GameResult::Win(result).fair_random_result(),
-            None => continue,
-        }
-    }
-    return GameResult::Draw;
-}
\ No newline at end of file
+use log::debug;
+use rand::{Rng, RngCore};
+
+use crate::game::{move_set::MoveSet, result::GameResult, Game};
+
+// Picks each move of a playout from the legal moves, which are never empty
+pub trait PlayoutPolicy<G: Game>: Send + Sync {
+    fn choose(&self, board: &G, moves: &[usize], rng: &mut dyn RngCore) -> usize;
+}
+
+pub struct UniformRandom;
+
+// Wins if it can, otherwise avoids moves that let the opponent win straight away, which is how forced blocks get played
+pub struct Tactical;
+
+// Samples moves in proportion to the game's priors, favouring the centre on connect boards
+pub struct CentreWeighted;
+
+// Plays the move with the best static evaluation, or a random one with probability epsilon
+pub struct EpsilonGreedy {
+    pub epsilon: f32,
+}
+
+impl<G: Game> PlayoutPolicy<G> for UniformRandom {
+    fn choose(&self, _board: &G, moves: &[usize], rng: &mut dyn RngCore) -> usize {
+        moves[rng.gen_range(0..moves.len())]
+    }
+}
+
+impl<G: Game> PlayoutPolicy<G> for Tactical {
+    fn choose(&self, board: &G, moves: &[usize], rng: &mut dyn RngCore) -> usize {
+        let mover = board.side_to_move();
+        let children: Vec<(usize, G)> = moves.iter().map(|m| (*m, board.apply_move(*m))).collect();
+        if let Some((m, _)) = children.iter().find(|(_, child)| child.result() == Some(GameResult::Win(mover))) {
+            return *m;
+        }
+
+        let safe: Vec<usize> = children
+            .iter()
+            .filter(|(_, child)| !gives_immediate_win(child))
+            .map(|(m, _)| *m)
+            .collect();
+        match safe.is_empty() {
+            true => moves[rng.gen_range(0..moves.len())],
+            false => safe[rng.gen_range(0..safe.len())],
+        }
+    }
+}
+
+impl<G: Game> PlayoutPolicy<G> for CentreWeighted {
+    fn choose(&self, board: &G, moves: &[usize], rng: &mut dyn RngCore) -> usize {
+        let priors = board.priors();
+        let total: f32 = moves.iter().map(|m| priors[*m]).sum();
+        let mut target = rng.gen::<f32>() * total;
+        for m in moves {
+            target -= priors[*m];
+            if target <= 0.0 {
+                return *m;
+            }
+        }
+        moves[moves.len() - 1]
+    }
+}
+
+impl<G: Game> PlayoutPolicy<G> for EpsilonGreedy {
+    fn choose(&self, board: &G, moves: &[usize], rng: &mut dyn RngCore) -> usize {
+        if rng.gen::<f32>() < self.epsilon {
+            return moves[rng.gen_range(0..moves.len())];
+        }
+        let mover = board.side_to_move();
+        let mut best = (f32::MIN, moves[0]);
+        for m in moves {
+            let child = board.apply_move(*m);
+            let score = match child.result() {
+                Some(GameResult::Win(winner)) if winner == mover => f32::MAX,
+                Some(GameResult::Win(_)) => f32::MIN,
+                Some(GameResult::Draw) => 0.0,
+                // The child is evaluated for the opponent
+                None => -child.evaluate(),
+            };
+            if score > best.0 {
+                best = (score, *m);
+            }
+        }
+        best.1
+    }
+}
+
+fn gives_immediate_win<G: Game>(board: &G) -> bool {
+    let mover = board.side_to_move();
+    let wins = |m: usize| board.apply_move(m).result() == Some(GameResult::Win(mover));
+    match G::ACTIONS <= MoveSet::CAPACITY {
+        true => board.move_set().any(wins),
+        false => board.legal_moves().into_iter().any(wins),
+    }
+}
+
+// Refills `legal` from the move set when the game's actions fit in one, saving an allocation
+fn fill_legal_moves<G: Game>(board: &G, legal: &mut Vec<usize>) {
+    legal.clear();
+    match G::ACTIONS <= MoveSet::CAPACITY {
+        true => legal.extend(board.move_set()),
+        false => legal.extend(board.legal_moves()),
+    }
+}
+
+// Policy by name, as used in tournament configurations: uniform, tactical, centre or greedy
+pub fn policy_from_name<G: Game>(name: &str, epsilon: f32) -> Option<Box<dyn PlayoutPolicy<G>>> {
+    match name {
+        "uniform" => Some(Box::new(UniformRandom)),
+        "tactical" => Some(Box::new(Tactical)),
+        "centre" => Some(Box::new(CentreWeighted)),
+        "greedy" => Some(Box::new(EpsilonGreedy { epsilon })),
+        _ => None,
+    }
+}
+
+// Every move played is appended to `moves`, for all-moves-as-first statistics
+pub fn from<G: Game>(mut board: G, policy: &dyn PlayoutPolicy<G>, rng: &mut dyn RngCore, moves: &mut Vec<usize>) -> GameResult {
+    if let Some(r) = board.result() {
+        return r;
+    }
+    // Refilled each ply rather than allocating a fresh list
+    let mut legal = Vec::with_capacity(G::ACTIONS);
+    for i in 0..1000 {
+        fill_legal_moves(&board, &mut legal);
+        if legal.is_empty() {
+            debug!("Trying to simulate state with no moves at depth {i}: {board:?}");
+            return GameResult::Draw;
+        }
+
+        let m = policy.choose(&board, &legal, rng);
+        moves.push(m);
+        board = board.apply_move(m);
+
+        match board.result() {
+            Some(result) => return result,
+            None => continue,
+        }
+    }
+    GameResult::Draw
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::game::{board::Board, player::Player};
+
+    use super::*;
+
+    #[test]
+    pub fn playout_records_the_moves_it_plays() {
+        // Arrange
+        let b = Board::from_position("7/7/7/7/7/yyy1bbb y").unwrap();
+        let mut moves = vec![];
+
+        // Act
+        let result = from(b, &Tactical, &mut StdRng::seed_from_u64(7), &mut moves);
+
+        // Assert
+        assert_eq!(result, GameResult::Win(Player::Yellow));
+        assert_eq!(moves, vec![3]);
+    }
+
+    // Players take turns claiming one of 100 cells, whoever claims the last cell wins
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Claim {
+        taken: u128,
+    }
+
+    impl Game for Claim {
+        const ACTIONS: usize = 100;
+        type Key = u128;
+
+        fn legal_moves(&self) -> Vec<usize> {
+            match self.result() {
+                Some(_) => vec![],
+                None => (0..Self::ACTIONS).filter(|m| self.taken >> m & 1 == 0).collect(),
+            }
+        }
+
+        fn apply_move(&self, index: usize) -> Self {
+            Self { taken: self.taken | 1 << index }
+        }
+
+        fn result(&self) -> Option<GameResult> {
+            match self.taken >> (Self::ACTIONS - 1) & 1 {
+                // The side to move has just lost the last cell to the other
+                1 => Some(GameResult::Win(self.side_to_move().invert())),
+                _ => None,
+            }
+        }
+
+        fn side_to_move(&self) -> Player {
+            match self.taken.count_ones() % 2 {
+                0 => Player::Yellow,
+                _ => Player::Blue,
+            }
+        }
+
+        fn key(&self) -> u128 {
+            self.taken
+        }
+    }
+
+    #[test]
+    pub fn playouts_cover_games_with_more_actions_than_a_move_set_holds() {
+        // Arrange
+        let mut rng = StdRng::seed_from_u64(7);
+        let (mut uniform, mut tactical) = (vec![], vec![]);
+
+        // Act
+        let uniform_result = from(Claim::default(), &UniformRandom, &mut rng, &mut uniform);
+        let tactical_result = from(Claim::default(), &Tactical, &mut rng, &mut tactical);
+
+        // Assert
+        assert!(matches!(uniform_result, GameResult::Win(_)));
+        assert_eq!(uniform.last(), Some(&99));
+        assert!(uniform.iter().any(|m| *m >= MoveSet::CAPACITY));
+        assert_eq!(tactical_result, GameResult::Win(Player::Yellow));
+        assert_eq!(tactical, vec![99]);
+    }
+
+    #[test]
+    pub fn tactical_takes_the_win() {
+        // Arrange
+        let b = Board::from_position("7/7/7/7/7/yyy1bbb y").unwrap();
+
+        // Act
+        let m = Tactical.choose(&b, &b.legal_moves(), &mut StdRng::seed_from_u64(7));
+
+        // Assert
+        assert_eq!(m, 3);
+    }
+
+    #[test]
+    pub fn tactical_blocks_the_opponent() {
+        // Arrange
+        let b = Board::from_position("7/7/7/7/7/yyy2bb b").unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // Act
+        let moves: Vec<usize> = (0..20).map(|_| Tactical.choose(&b, &b.legal_moves(), &mut rng)).collect();
+
+        // Assert
+        assert!(moves.iter().all(|m| *m == 3));
+    }
+
+    #[test]
+    pub fn centre_weighted_only_plays_legal_moves_and_favours_the_centre() {
+        // Arrange
+        let b = Board::from_moves("777777").unwrap();
+        let moves = vec![0, 1, 2, 3, 4, 5];
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // Act
+        let picks: Vec<usize> = (0..2000).map(|_| CentreWeighted.choose(&b, &moves, &mut rng)).collect();
+
+        // Assert
+        assert!(picks.iter().all(|m| moves.contains(m)));
+        let centre = picks.iter().filter(|m| **m == 3).count();
+        let edge = picks.iter().filter(|m| **m == 0).count();
+        assert!(centre > edge);
+    }
+
+    #[test]
+    pub fn greedy_without_exploration_plays_the_centre_first() {
+        // Arrange
+        let b = Board::default();
+        let policy = EpsilonGreedy { epsilon: 0.0 };
+
+        // Act
+        let m = policy.choose(&b, &b.legal_moves(), &mut StdRng::seed_from_u64(7));
+
+        // Assert
+        assert_eq!(m, 3);
+    }
+}
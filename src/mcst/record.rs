@@ -1,23 +1,159 @@
-use crate::game::result::GameResult;
-
-#[derive(Clone, Copy, Debug, Default)]
-pub struct Record {
-    pub wins: u64,
-    pub played: u64,
-}
-
-impl Record {
-    pub fn increment(&mut self, win: bool) {
-        self.played += 1;
-        self.wins += match win {
-            true => 1,
-            _ => 0,
-        }
-    }
-}
-
-impl std::fmt::Display for Record {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(format!("{}/{}", self.wins, self.played).as_str())
-    }
-}
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Record {
+    pub wins: u64,
+    pub draws: u64,
+    pub played: u64,
+}
+
+impl Record {
+    pub fn increment(&mut self, win: bool) {
+        self.played += 1;
+        self.wins += match win {
+            true => 1,
+            _ => 0,
+        }
+    }
+
+    pub fn increment_draw(&mut self) {
+        self.played += 1;
+        self.draws += 1;
+    }
+
+    // Draws count as half a win
+    pub fn score(&self) -> f32 {
+        self.wins as f32 + self.draws as f32 / 2.0
+    }
+
+    pub fn mean(&self) -> f32 {
+        self.score() / self.played as f32
+    }
+
+    // Rewards are only ever 0, 1/2 or 1 so the sum of their squares follows from the counts
+    pub fn sum_squares(&self) -> f32 {
+        self.wins as f32 + self.draws as f32 / 4.0
+    }
+
+    pub fn variance(&self) -> f32 {
+        let mean = self.mean();
+        (self.sum_squares() / self.played as f32 - mean * mean).max(0.0)
+    }
+
+    // Wilson score interval around the mean, z = 1.96 gives 95% confidence
+    pub fn confidence_interval(&self, z: f32) -> (f32, f32) {
+        if self.played == 0 {
+            return (0.0, 1.0);
+        }
+        let n = self.played as f32;
+        let p = self.mean();
+        let centre = p + z * z / (2.0 * n);
+        let margin = z * f32::sqrt(p * (1.0 - p) / n + z * z / (4.0 * n * n));
+        let denominator = 1.0 + z * z / n;
+        (((centre - margin) / denominator).max(0.0), ((centre + margin) / denominator).min(1.0))
+    }
+}
+
+// Record shared between search threads. Virtual losses are added while a playout through the node is in
+// flight so other threads are steered elsewhere, and show up as losses until the real result arrives.
+#[derive(Debug, Default)]
+pub struct AtomicRecord {
+    wins: AtomicU64,
+    draws: AtomicU64,
+    played: AtomicU64,
+    virtual_losses: AtomicU64,
+}
+
+impl AtomicRecord {
+    pub fn load(&self) -> Record {
+        Record {
+            wins: self.wins.load(Ordering::Relaxed),
+            draws: self.draws.load(Ordering::Relaxed),
+            played: self.played.load(Ordering::Relaxed) + self.virtual_losses.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn increment(&self, win: bool) {
+        if win {
+            self.wins.fetch_add(1, Ordering::Relaxed);
+        }
+        self.played.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_draw(&self) {
+        self.draws.fetch_add(1, Ordering::Relaxed);
+        self.played.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_virtual_loss(&self) {
+        self.virtual_losses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn remove_virtual_loss(&self) {
+        self.virtual_losses.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl std::fmt::Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(format!("{}/{}/{}", self.wins, self.draws, self.played).as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    pub fn draws_count_as_half_a_win() {
+        // Arrange
+        let mut record = Record::default();
+
+        // Act
+        record.increment(true);
+        record.increment_draw();
+        record.increment_draw();
+        record.increment(false);
+
+        // Assert
+        assert_eq!(record.played, 4);
+        assert_eq!(record.score(), 2.0);
+        assert_eq!(record.mean(), 0.5);
+        assert_eq!(record.sum_squares(), 1.5);
+        assert_eq!(record.variance(), 0.125);
+    }
+
+    #[test]
+    pub fn confidence_interval_narrows_with_more_playouts() {
+        // Arrange
+        let few = Record { wins: 6, draws: 0, played: 10 };
+        let many = Record { wins: 600, draws: 0, played: 1000 };
+
+        // Act
+        let (few_low, few_high) = few.confidence_interval(1.96);
+        let (many_low, many_high) = many.confidence_interval(1.96);
+
+        // Assert
+        assert!(few_low < 0.6 && 0.6 < few_high);
+        assert!(many_low < 0.6 && 0.6 < many_high);
+        assert!(many_high - many_low < few_high - few_low);
+        assert!((many_low - 0.569).abs() < 0.001);
+    }
+
+    #[test]
+    pub fn virtual_losses_count_until_removed() {
+        // Arrange
+        let record = AtomicRecord::default();
+        record.increment(true);
+
+        // Act
+        record.add_virtual_loss();
+        let during = record.load();
+        record.remove_virtual_loss();
+        let after = record.load();
+
+        // Assert
+        assert_eq!(during.mean(), 0.5);
+        assert_eq!(after.mean(), 1.0);
+    }
+}
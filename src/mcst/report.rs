@@ -0,0 +1,111 @@
+use std::{collections::HashSet, fmt, time::Duration};
+
+use crate::game::{result::GameResult, Game};
+
+use super::{budget::SearchProgress, node::ArcNode, valid_move::ValidMove, MoveStats};
+
+// Z score for the 95% confidence intervals shown on each move
+pub const CONFIDENCE_Z: f32 = 1.96;
+
+#[derive(Clone, Debug)]
+pub struct SearchReport {
+    pub best_move: Option<usize>,
+    // Most visited line from the root
+    pub principal_variation: Vec<usize>,
+    pub moves: Vec<MoveStats>,
+    pub root_result: Option<GameResult>,
+    // Distinct nodes reachable from the root and the deepest of them
+    pub tree_size: usize,
+    pub depth: usize,
+    // Work done by the most recent search
+    pub last_search: SearchProgress,
+}
+
+impl SearchReport {
+    pub fn playouts_per_second(&self) -> f64 {
+        let seconds = self.last_search.elapsed.as_secs_f64();
+        match seconds > 0.0 {
+            true => self.last_search.playouts as f64 / seconds,
+            false => 0.0,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.last_search.elapsed
+    }
+}
+
+pub(super) fn principal_variation<G: Game>(root: &ArcNode<G>) -> Vec<usize> {
+    let mut line = vec![];
+    let mut node = root.clone();
+    while let Some(children) = node.children.get() {
+        let most_visited = children
+            .iter()
+            .enumerate()
+            .filter_map(|(index, child)| match child {
+                ValidMove::Valid(c) => Some((index, c)),
+                ValidMove::Invalid => None,
+            })
+            .max_by_key(|(_, c)| c.record.load().played);
+        match most_visited {
+            Some((index, child)) if child.record.load().played > 0 => {
+                line.push(index);
+                node = child.clone();
+            }
+            _ => break,
+        }
+    }
+    line
+}
+
+// Size and depth of the tree below root, counting nodes shared by transpositions once
+pub(super) fn measure<G: Game>(root: &ArcNode<G>) -> (usize, usize) {
+    let mut seen = HashSet::new();
+    let mut pending = vec![(root.clone(), 0)];
+    let mut depth = 0;
+    while let Some((node, d)) = pending.pop() {
+        if !seen.insert(std::sync::Arc::as_ptr(&node)) {
+            continue;
+        }
+        depth = depth.max(d);
+        if let Some(children) = node.children.get() {
+            for child in children.iter() {
+                if let ValidMove::Valid(c) = child {
+                    pending.push((c.clone(), d + 1));
+                }
+            }
+        }
+    }
+    (seen.len(), depth)
+}
+
+impl fmt::Display for SearchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "State winner: {:?}", self.root_result)?;
+        for m in self.moves.iter() {
+            let (low, high) = m.record.confidence_interval(CONFIDENCE_Z);
+            writeln!(
+                f,
+                "Option {}: {} - {:.3} [{low:.3}, {high:.3}] - {:?}",
+                m.index,
+                m.record,
+                m.record.mean(),
+                m.result
+            )?;
+        }
+        let pv: Vec<String> = self.principal_variation.iter().map(|m| m.to_string()).collect();
+        writeln!(f, "Principal variation: {}", pv.join(" "))?;
+        writeln!(f, "Tree: {} nodes, depth {}", self.tree_size, self.depth)?;
+        writeln!(
+            f,
+            "Last search: {} playouts in {:.2?} ({:.0} playouts/s)",
+            self.last_search.playouts,
+            self.last_search.elapsed,
+            self.playouts_per_second()
+        )?;
+        match self.best_move {
+            Some(m) => write!(f, "Expected move: {m}"),
+            None => write!(f, "Unexplored root"),
+        }
+    }
+}
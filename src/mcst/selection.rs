@@ -0,0 +1,291 @@
+use std::f32::consts::{PI, SQRT_2};
+
+use rand::{Rng, RngCore};
+
+use super::record::Record;
+
+// What a policy gets to see when scoring a move during selection
+#[derive(Clone, Copy, Debug)]
+pub struct Candidate {
+    pub parent: Record,
+    pub child: Record,
+    pub prior: f32,
+    // All-moves-as-first statistics for the move, empty unless the policy uses them
+    pub amaf: Record,
+}
+
+// Scores moves during selection, the highest scoring leaf gets expanded
+pub trait SelectionPolicy: Send + Sync {
+    fn score(&self, candidate: &Candidate, rng: &mut dyn RngCore) -> f32;
+
+    // Whether the tree needs to gather all-moves-as-first statistics for this policy
+    fn uses_amaf(&self) -> bool {
+        false
+    }
+}
+
+pub struct Ucb1 {
+    pub exploration: f32,
+}
+
+// Scales exploration by the observed variance of the move's results
+pub struct Ucb1Tuned {
+    pub exploration: f32,
+}
+
+// AlphaZero style prior weighted exploration
+pub struct Puct {
+    pub exploration: f32,
+}
+
+// Samples each move's win rate from its Beta posterior
+pub struct ThompsonSampling;
+
+// UCB1 over a blend of the move's own mean and its all-moves-as-first mean, leaning on the latter while
+// the move has few visits of its own
+pub struct Rave {
+    pub exploration: f32,
+    pub schedule: BetaSchedule,
+}
+
+// Weight given to the all-moves-as-first mean
+#[derive(Clone, Copy, Debug)]
+pub enum BetaSchedule {
+    // sqrt(k / (3n + k)), equal weighting after k visits
+    Equivalence(f32),
+    // Silver's minimum MSE schedule with the given bias between the two estimates
+    MinimumMse(f32),
+}
+
+impl BetaSchedule {
+    pub fn beta(&self, played: u64, amaf_played: u64) -> f32 {
+        let (n, amaf_n) = (played as f32, amaf_played as f32);
+        match *self {
+            BetaSchedule::Equivalence(k) => f32::sqrt(k / (3.0 * n + k)),
+            BetaSchedule::MinimumMse(bias) => {
+                if amaf_played == 0 {
+                    return 0.0;
+                }
+                amaf_n / (n + amaf_n + 4.0 * bias * bias * n * amaf_n)
+            }
+        }
+    }
+}
+
+impl Default for Ucb1 {
+    fn default() -> Self {
+        Self { exploration: SQRT_2 }
+    }
+}
+
+impl SelectionPolicy for Ucb1 {
+    fn score(&self, candidate: &Candidate, _rng: &mut dyn RngCore) -> f32 {
+        let (parent, child) = (candidate.parent, candidate.child);
+        if child.played == 0 {
+            return f32::INFINITY;
+        }
+        let exploration_bias = self.exploration * f32::sqrt(f32::ln(parent.played as f32) / child.played as f32);
+        child.mean() + exploration_bias
+    }
+}
+
+impl SelectionPolicy for Ucb1Tuned {
+    fn score(&self, candidate: &Candidate, _rng: &mut dyn RngCore) -> f32 {
+        let (parent, child) = (candidate.parent, candidate.child);
+        if child.played == 0 {
+            return f32::INFINITY;
+        }
+        let log_parent = f32::ln(parent.played as f32);
+        let variance_bound = child.variance() + f32::sqrt(2.0 * log_parent / child.played as f32);
+        child.mean() + self.exploration * f32::sqrt(log_parent / child.played as f32 * variance_bound.min(0.25))
+    }
+}
+
+impl SelectionPolicy for Puct {
+    fn score(&self, candidate: &Candidate, _rng: &mut dyn RngCore) -> f32 {
+        let (parent, child) = (candidate.parent, candidate.child);
+        let value = if child.played == 0 { 0.0 } else { child.mean() };
+        value + self.exploration * candidate.prior * f32::sqrt(parent.played as f32) / (1.0 + child.played as f32)
+    }
+}
+
+impl SelectionPolicy for ThompsonSampling {
+    fn score(&self, candidate: &Candidate, rng: &mut dyn RngCore) -> f32 {
+        let child = candidate.child;
+        let successes = child.score();
+        sample_beta(successes + 1.0, child.played as f32 - successes + 1.0, rng)
+    }
+}
+
+impl Default for Rave {
+    fn default() -> Self {
+        Self { exploration: SQRT_2, schedule: BetaSchedule::Equivalence(1000.0) }
+    }
+}
+
+impl SelectionPolicy for Rave {
+    fn score(&self, candidate: &Candidate, _rng: &mut dyn RngCore) -> f32 {
+        let (parent, child, amaf) = (candidate.parent, candidate.child, candidate.amaf);
+        if child.played == 0 {
+            return f32::INFINITY;
+        }
+        let value = match amaf.played {
+            0 => child.mean(),
+            _ => {
+                let beta = self.schedule.beta(child.played, amaf.played);
+                (1.0 - beta) * child.mean() + beta * amaf.mean()
+            }
+        };
+        value + self.exploration * f32::sqrt(f32::ln(parent.played as f32) / child.played as f32)
+    }
+
+    fn uses_amaf(&self) -> bool {
+        true
+    }
+}
+
+// Policy by name, as used in tournament configurations: ucb1, ucb1-tuned, puct, thompson or rave
+pub fn policy_from_name(name: &str, exploration: f32) -> Option<Box<dyn SelectionPolicy>> {
+    match name {
+        "ucb1" => Some(Box::new(Ucb1 { exploration })),
+        "ucb1-tuned" => Some(Box::new(Ucb1Tuned { exploration })),
+        "puct" => Some(Box::new(Puct { exploration })),
+        "thompson" => Some(Box::new(ThompsonSampling)),
+        "rave" => Some(Box::new(Rave { exploration, ..Rave::default() })),
+        _ => None,
+    }
+}
+
+fn sample_beta(a: f32, b: f32, rng: &mut dyn RngCore) -> f32 {
+    let x = sample_gamma(a, rng);
+    let y = sample_gamma(b, rng);
+    x / (x + y)
+}
+
+// Marsaglia and Tsang's method, valid for shape >= 1 which always holds for Beta(successes + 1, failures + 1)
+fn sample_gamma(shape: f32, rng: &mut dyn RngCore) -> f32 {
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / f32::sqrt(9.0 * d);
+    loop {
+        let x = sample_normal(rng);
+        let v = (1.0 + c * x).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u: f32 = rng.gen();
+        if f32::ln(u) < 0.5 * x * x + d - d * v + d * f32::ln(v) {
+            return d * v;
+        }
+    }
+}
+
+fn sample_normal(rng: &mut dyn RngCore) -> f32 {
+    let u1: f32 = 1.0 - rng.gen::<f32>();
+    let u2: f32 = rng.gen();
+    f32::sqrt(-2.0 * f32::ln(u1)) * f32::cos(2.0 * PI * u2)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn candidate(wins: u64, played: u64, parent_played: u64, prior: f32) -> Candidate {
+        Candidate {
+            parent: Record { wins: 0, draws: 0, played: parent_played },
+            child: Record { wins, draws: 0, played },
+            prior,
+            amaf: Record::default(),
+        }
+    }
+
+    #[test]
+    pub fn ucb1_matches_formula() {
+        // Arrange
+        let policy = Ucb1 { exploration: 2.0 };
+        let c = candidate(5, 10, 100, 0.0);
+
+        // Act
+        let score = policy.score(&c, &mut StdRng::seed_from_u64(7));
+
+        // Assert
+        let expected = 0.5 + 2.0 * f32::sqrt(f32::ln(100.0) / 10.0);
+        assert!((score - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn ucb1_tuned_explores_less_than_ucb1_for_low_variance_moves() {
+        // Arrange
+        let c = candidate(10, 10, 100, 0.0);
+
+        // Act
+        let tuned = Ucb1Tuned { exploration: 1.0 }.score(&c, &mut StdRng::seed_from_u64(7));
+        let plain = Ucb1 { exploration: 1.0 }.score(&c, &mut StdRng::seed_from_u64(7));
+
+        // Assert
+        assert!(tuned < plain);
+        assert!(tuned > 1.0);
+    }
+
+    #[test]
+    pub fn puct_prefers_higher_prior_with_equal_records() {
+        // Arrange
+        let policy = Puct { exploration: 1.5 };
+
+        // Act
+        let likely = policy.score(&candidate(3, 6, 50, 0.4), &mut StdRng::seed_from_u64(7));
+        let unlikely = policy.score(&candidate(3, 6, 50, 0.1), &mut StdRng::seed_from_u64(7));
+
+        // Assert
+        assert!(likely > unlikely);
+    }
+
+    #[test]
+    pub fn thompson_samples_centre_on_win_rate() {
+        // Arrange
+        let c = candidate(80, 100, 200, 0.0);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // Act
+        let samples: Vec<f32> = (0..2000).map(|_| ThompsonSampling.score(&c, &mut rng)).collect();
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+
+        // Assert
+        assert!(samples.iter().all(|s| (0.0..=1.0).contains(s)));
+        assert!((mean - 81.0 / 102.0).abs() < 0.02);
+    }
+
+    #[test]
+    pub fn rave_leans_on_amaf_until_the_move_has_visits_of_its_own() {
+        // Arrange
+        let policy = Rave { exploration: 0.0, schedule: BetaSchedule::Equivalence(100.0) };
+        let amaf = Record { wins: 90, draws: 0, played: 100 };
+        let young = Candidate { amaf, ..candidate(1, 2, 1000, 0.0) };
+        let old = Candidate { amaf, ..candidate(5000, 10000, 20000, 0.0) };
+
+        // Act
+        let young_score = policy.score(&young, &mut StdRng::seed_from_u64(7));
+        let old_score = policy.score(&old, &mut StdRng::seed_from_u64(7));
+
+        // Assert
+        assert!(young_score > 0.85);
+        assert!((old_score - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    pub fn minimum_mse_schedule_ignores_missing_amaf() {
+        assert_eq!(BetaSchedule::MinimumMse(0.1).beta(10, 0), 0.0);
+        assert!(BetaSchedule::MinimumMse(0.1).beta(10, 100) > BetaSchedule::MinimumMse(0.1).beta(100, 100));
+    }
+
+    #[test]
+    pub fn policies_are_found_by_name() {
+        assert!(policy_from_name("ucb1", 1.0).is_some());
+        assert!(policy_from_name("ucb1-tuned", 1.0).is_some());
+        assert!(policy_from_name("puct", 1.0).is_some());
+        assert!(policy_from_name("thompson", 1.0).is_some());
+        assert!(policy_from_name("rave", 1.0).unwrap().uses_amaf());
+        assert!(policy_from_name("greedy", 1.0).is_none());
+    }
+}
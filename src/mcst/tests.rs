@@ -1,57 +1,395 @@
-use crate::{game::board::Board, mcst::SearchTree};
-
-#[test]
-pub fn winning_move_possible() {
-    // Act
-    let mut tree = SearchTree::new(Board::setup(7, 112, [1, 1, 1, 0, 1, 1, 1]), 5);
-
-    // Act
-    for i in 0..10 {
-        tree.iterate();
-    }
-
-    let m = tree.choose_move();
-
-    // Assert
-    assert_eq!(m, 3);
-}
-
-#[test]
-pub fn opponent_can_win_next_move_should_block() {
-    // Act
-    let b = Board::setup(7, 96, [1, 1, 0, 0, 1, 1, 1]);
-    let mut tree = SearchTree::new(b, 5);
-
-    // Act
-    for i in 0..8 {
-        tree.iterate();
-    }
-
-    tree.print_state();
-    b.print_board();
-
-    let m = tree.choose_move();
-
-    // Assert
-    assert_eq!(m, 3);
-}
-
-#[test]
-pub fn opponent_can_win_next_move_should_but_so_can_player_should_win() {
-    // Act
-    let b = Board::setup(16513, 14, [3, 1, 1, 1, 0, 0, 0]);
-    let mut tree = SearchTree::new(b, 5);
-
-    // Act
-    for i in 0..50 {
-        tree.iterate();
-    }
-
-    tree.print_state();
-    b.print_board();
-
-    let m = tree.choose_move();
-
-    // Assert
-    assert_eq!(m, 0);
-}
+use std::{sync::Arc, time::Duration};
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    game::{board::Board, player::Player, result::GameResult, tic_tac_toe::TicTacToe, Game},
+    mcst::{
+        budget::SearchBudget, choose_from, error::SearchError, node::ArcNode, playout, record::Record, selection::policy_from_name,
+        valid_move::ValidMove, MoveStats, SearchTree,
+    },
+};
+
+#[test]
+pub fn winning_move_possible() {
+    // Act
+    let mut tree = SearchTree::new(Board::from_position("7/7/7/7/7/yyy1bbb y").unwrap(), 5);
+
+    // Act
+    for _ in 0..10 {
+        tree.iterate();
+    }
+
+    let m = tree.choose_move();
+
+    // Assert
+    assert_eq!(m, 3);
+}
+
+#[test]
+pub fn opponent_can_win_next_move_should_block() {
+    // Act
+    let b = Board::from_position("7/7/7/7/7/yyy2bb b").unwrap();
+    let mut tree = SearchTree::new(b, 5);
+    tree.rng = StdRng::seed_from_u64(3);
+
+    // Act
+    for _ in 0..1000 {
+        tree.iterate();
+    }
+
+    tree.print_state();
+    b.print_board();
+
+    let m = tree.choose_move();
+
+    // Assert
+    assert_eq!(m, 3);
+}
+
+#[test]
+pub fn opponent_can_win_next_move_should_but_so_can_player_should_win() {
+    // Act
+    let b = Board::from_position("7/7/7/y6/y6/ybbb3 y").unwrap();
+    let mut tree = SearchTree::new(b, 5);
+
+    // Act
+    for _ in 0..50 {
+        tree.iterate();
+    }
+
+    tree.print_state();
+    b.print_board();
+
+    let m = tree.choose_move();
+
+    // Assert
+    assert_eq!(m, 0);
+}
+
+#[test]
+pub fn drawing_move_preferred_over_losing_moves() {
+    // Arrange
+    // X X _ / O O X / _ _ _ with O to move: only blocking on 2 avoids a loss
+    let b = TicTacToe::setup(0x23, 0x18);
+    let mut tree = SearchTree::new(b, 5);
+
+    // Act
+    for _ in 0..50 {
+        tree.iterate();
+    }
+
+    tree.print_state();
+
+    let m = tree.choose_move();
+
+    // Assert
+    assert_eq!(m, 2);
+}
+
+#[test]
+pub fn proven_draw_preferred_over_poorly_scoring_moves() {
+    // Arrange
+    let stats = |index, wins, played, result| MoveStats { index, record: Record { wins, draws: 0, played }, result };
+    let losing = [stats(0, 1, 20, None), stats(1, 0, 0, None), stats(2, 0, 3, Some(GameResult::Draw))];
+    let promising = [stats(0, 15, 20, None), stats(2, 0, 3, Some(GameResult::Draw))];
+    let unplayed = [stats(0, 0, 0, None), stats(1, 0, 0, None)];
+
+    // Act
+    let from_losing = choose_from(&losing, Player::Yellow);
+    let from_promising = choose_from(&promising, Player::Yellow);
+    let from_unplayed = choose_from(&unplayed, Player::Yellow);
+
+    // Assert
+    assert_eq!(from_losing, Ok(2));
+    assert_eq!(from_promising, Ok(0));
+    assert!(from_unplayed.is_ok());
+    assert_eq!(choose_from(&[], Player::Yellow), Err(SearchError::NoLegalMoves));
+}
+
+#[test]
+pub fn proven_results_propagate_to_root() {
+    // Arrange
+    let b = TicTacToe::setup(0x23, 0x18);
+    let mut tree = SearchTree::new(b, 1);
+
+    // Act
+    for _ in 0..200 {
+        tree.iterate();
+    }
+
+    // Assert
+    let children = tree.root.children.get().unwrap();
+    let result = |i: usize| match &children[i] {
+        ValidMove::Valid(c) => c.result.get().copied(),
+        ValidMove::Invalid => None,
+    };
+    for i in [6, 7, 8] {
+        assert_eq!(result(i), Some(GameResult::Win(Player::Yellow)));
+    }
+    assert_eq!(result(2), Some(GameResult::Draw));
+    assert_eq!(tree.root.result.get(), Some(&GameResult::Draw));
+    assert_eq!(tree.choose_move(), 2);
+}
+
+#[test]
+pub fn search_stops_at_playout_budget() {
+    // Arrange
+    let mut tree = SearchTree::new(Board::default(), 5);
+
+    // Act
+    let progress = tree.search(SearchBudget::Playouts(200));
+
+    // Assert
+    assert!(progress.playouts >= 200);
+    // A single iteration adds at most one playout per simulation per move
+    assert!(progress.playouts < 200 + 5 * 7);
+    assert_eq!(progress.playouts, tree.playouts());
+}
+
+#[test]
+pub fn search_stops_at_time_budget() {
+    // Arrange
+    let mut tree = SearchTree::new(Board::default(), 5);
+
+    // Act
+    let progress = tree.search(SearchBudget::Time(Duration::from_millis(50)));
+
+    // Assert
+    // Only the lower bound is exact, a loaded machine can run well past the deadline
+    assert!(progress.elapsed >= Duration::from_millis(50));
+    assert!(progress.elapsed < Duration::from_secs(10));
+    assert!(progress.iterations > 0);
+    assert_eq!(progress.playouts, tree.playouts());
+}
+
+#[test]
+pub fn search_stops_early_once_root_is_proven() {
+    // Arrange
+    let mut tree = SearchTree::new(Board::from_position("7/7/7/7/7/yyy1bbb y").unwrap(), 5);
+
+    // Act
+    let progress = tree.search(SearchBudget::Iterations(1000));
+
+    // Assert
+    assert_eq!(progress.iterations, 1);
+    assert_eq!(tree.choose_move(), 3);
+}
+
+#[test]
+pub fn search_stops_early_once_best_move_cannot_be_overtaken() {
+    // Arrange
+    let mut tree = SearchTree::new(Board::default(), 5);
+    tree.early_stop = true;
+
+    // Act
+    let progress = tree.search(SearchBudget::Playouts(20_000));
+
+    // Assert
+    assert!(progress.playouts < 20_000);
+}
+
+#[test]
+pub fn parallel_search_shares_one_tree() {
+    // Arrange
+    let mut tree = SearchTree::new(Board::default(), 5);
+
+    // Act
+    let progress = tree.search_parallel(SearchBudget::Playouts(2000), 4);
+
+    // Assert
+    assert!(progress.playouts >= 2000);
+    // Every playout reached the root and no virtual losses were left behind
+    assert_eq!(tree.root.record.load().played as usize, tree.playouts());
+}
+
+#[test]
+pub fn parallel_search_blocks_opponent_win() {
+    // Act
+    let b = Board::from_position("7/7/7/7/7/yyy2bb b").unwrap();
+    let mut tree = SearchTree::new(b, 5);
+
+    // Act
+    tree.search_parallel(SearchBudget::Iterations(40), 4);
+
+    // Assert
+    assert_eq!(tree.choose_move(), 3);
+}
+
+fn child<G: Game>(node: &ArcNode<G>, index: usize) -> ArcNode<G> {
+    match &node.children.get().unwrap()[index] {
+        ValidMove::Valid(c) => c.clone(),
+        ValidMove::Invalid => panic!("move {index} is not valid"),
+    }
+}
+
+#[test]
+pub fn transpositions_share_a_node() {
+    // Arrange
+    let tree = SearchTree::with_transpositions(Board::default(), 1);
+    let expand = |moves: &[usize]| {
+        let mut node = tree.root.clone();
+        for m in moves {
+            tree.expansion(node.clone());
+            node = child(&node, *m);
+        }
+        node
+    };
+
+    // Act
+    let a = expand(&[0, 1, 2]);
+    let b = expand(&[2, 1, 0]);
+    let c = expand(&[0, 1, 3]);
+
+    // Assert
+    assert!(Arc::ptr_eq(&a, &b));
+    assert!(!Arc::ptr_eq(&a, &c));
+    assert_eq!(a.live_parents().len(), 2);
+}
+
+#[test]
+pub fn transposition_search_records_each_playout_once_at_root() {
+    // Arrange
+    let b = Board::from_position("7/7/7/7/7/yyy2bb b").unwrap();
+    let mut tree = SearchTree::with_transpositions(b, 5);
+
+    // Act
+    tree.search(SearchBudget::Iterations(40));
+
+    // Assert
+    assert_eq!(tree.root.record.load().played as usize, tree.playouts());
+    assert_eq!(tree.choose_move(), 3);
+}
+
+#[test]
+pub fn every_selection_policy_blocks_opponent_win() {
+    for name in ["ucb1", "ucb1-tuned", "puct", "thompson", "rave"] {
+        // Arrange
+        let b = Board::from_position("7/7/7/7/7/yyy2bb b").unwrap();
+        let mut tree = SearchTree::new(b, 5);
+        tree.selection_policy = policy_from_name(name, 1.5).unwrap();
+
+        // Act
+        tree.search(SearchBudget::Iterations(200));
+
+        // Assert
+        assert_eq!(tree.choose_move(), 3, "{name} failed to block");
+    }
+}
+
+#[test]
+pub fn expansion_stores_priors_from_the_game() {
+    // Arrange
+    let tree = SearchTree::new(Board::default(), 1);
+
+    // Act
+    tree.expansion(tree.root.clone());
+
+    // Assert
+    let priors = tree.root.priors.get().unwrap();
+    assert_eq!(priors.len(), 7);
+    assert!((priors.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+    assert!(priors[3] > priors[0]);
+}
+
+#[test]
+pub fn every_playout_policy_blocks_opponent_win() {
+    for name in ["uniform", "tactical", "centre", "greedy"] {
+        // Arrange
+        let b = Board::from_position("7/7/7/7/7/yyy2bb b").unwrap();
+        let mut tree = SearchTree::new(b, 5);
+        tree.playout_policy = playout::policy_from_name(name, 0.1).unwrap();
+
+        // Act
+        tree.search(SearchBudget::Iterations(200));
+
+        // Assert
+        assert_eq!(tree.choose_move(), 3, "{name} failed to block");
+    }
+}
+
+#[test]
+pub fn rave_search_gathers_amaf_statistics_at_the_root() {
+    // Arrange
+    let mut tree = SearchTree::new(Board::default(), 2);
+    tree.selection_policy = policy_from_name("rave", 1.0).unwrap();
+
+    // Act
+    tree.search(SearchBudget::Iterations(20));
+
+    // Assert
+    let amaf = tree.root.amaf.get().unwrap();
+    let played: u64 = amaf.iter().map(|r| r.load().played).sum();
+    // Every playout counts each of yellow's columns at the root at least once
+    assert!(played >= tree.root.record.load().played);
+    assert!(child(&tree.root, 3).amaf.get().is_some());
+}
+
+#[test]
+pub fn report_describes_the_search() {
+    // Arrange
+    let b = Board::from_position("7/7/7/7/7/yyy2bb b").unwrap();
+    let mut tree = SearchTree::new(b, 5);
+
+    // Act
+    let progress = tree.search(SearchBudget::Iterations(100));
+    let report = tree.report();
+
+    // Assert
+    assert_eq!(report.best_move, Some(3));
+    assert_eq!(report.principal_variation.first(), Some(&3));
+    assert!(report.principal_variation.len() > 1);
+    assert_eq!(report.moves.len(), 7);
+    assert_eq!(report.tree_size, tree.nodes() + 1);
+    assert!(report.depth >= report.principal_variation.len());
+    assert_eq!(report.last_search.playouts, progress.playouts);
+    assert!(report.playouts_per_second() > 0.0);
+    assert!(report.to_string().contains("Expected move: 3"));
+}
+
+#[test]
+pub fn report_on_unexplored_root_has_no_moves() {
+    // Act
+    let report = SearchTree::new(Board::default(), 1).report();
+
+    // Assert
+    assert_eq!(report.best_move, None);
+    assert!(report.principal_variation.is_empty());
+    assert_eq!(report.tree_size, 1);
+    assert_eq!(report.depth, 0);
+}
+
+#[test]
+pub fn same_seed_searches_identically() {
+    // Arrange
+    let mut first = SearchTree::new(Board::default(), 3);
+    let mut second = SearchTree::new(Board::default(), 3);
+    first.rng = StdRng::seed_from_u64(11);
+    second.rng = StdRng::seed_from_u64(11);
+
+    // Act
+    first.search(SearchBudget::Iterations(50));
+    second.search(SearchBudget::Iterations(50));
+
+    // Assert
+    let visits = |tree: &SearchTree<Board>| tree.root_moves().iter().map(|m| m.record.to_string()).collect::<Vec<_>>();
+    assert_eq!(visits(&first), visits(&second));
+}
+
+#[test]
+pub fn tree_reports_errors_instead_of_panicking() {
+    // Arrange
+    let b = Board::from_moves("111111").unwrap();
+    let mut tree = SearchTree::new(b, 1);
+
+    // Act
+    let unexpanded = tree.try_choose_move();
+    let full_column = tree.try_record_move(0, b);
+    let off_board = tree.try_record_move(9, b);
+
+    // Assert
+    assert_eq!(unexpanded, Err(SearchError::Unexpanded));
+    assert_eq!(full_column.err(), Some(SearchError::InvalidMove(0)));
+    assert_eq!(off_board.err(), Some(SearchError::InvalidMove(9)));
+    assert!(tree.try_record_move(1, b.play_move(1)).is_ok());
+}
@@ -0,0 +1,52 @@
+use core::fmt;
+use std::io;
+
+use crate::game::error::PositionError;
+
+#[derive(Debug)]
+pub enum OpeningError {
+    Io(io::Error),
+    // Lines are numbered from 1
+    Invalid { line: usize, error: PositionError },
+    // The opening leaves no game to play
+    Finished { line: usize },
+    Empty,
+}
+
+impl fmt::Display for OpeningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpeningError::Io(e) => write!(f, "couldn't read the opening book: {e}"),
+            OpeningError::Invalid { line, error } => write!(f, "opening on line {line} is invalid: {error}"),
+            OpeningError::Finished { line } => write!(f, "opening on line {line} has already been decided"),
+            OpeningError::Empty => write!(f, "the opening book has no openings"),
+        }
+    }
+}
+
+impl std::error::Error for OpeningError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RatingError {
+    // The anchor's name isn't among the entrants
+    MissingAnchor(String),
+    // Without some virtual draws a winless or unbeaten entrant has no finite rating
+    InvalidPrior(f64),
+}
+
+impl fmt::Display for RatingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RatingError::MissingAnchor(name) => write!(f, "the anchor {name} didn't take part"),
+            RatingError::InvalidPrior(prior) => write!(f, "prior draws must be positive, not {prior}"),
+        }
+    }
+}
+
+impl std::error::Error for RatingError {}
+
+impl From<io::Error> for OpeningError {
+    fn from(e: io::Error) -> Self {
+        OpeningError::Io(e)
+    }
+}
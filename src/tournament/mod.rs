@@ -0,0 +1,315 @@
+use crate::{
+    agent::{Agent, Decision},
+    game::{board::Board, error::MoveError, history::History, player::Player, result::GameResult, Game},
+    mcst::error::SearchError,
+};
+
+pub mod error;
+pub mod openings;
+pub mod rating;
+pub mod round_robin;
+pub mod sprt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ForfeitReason {
+    NoMove(SearchError),
+    IllegalMove(MoveError),
+    // Asked for a take-back before making a move of their own, or once the game's allowance ran out
+    TakeBack,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Forfeit {
+    pub player: Player,
+    pub reason: ForfeitReason,
+}
+
+#[derive(Clone, Debug)]
+pub struct GameOutcome {
+    // Position when the game ended, short of a connection if it was forfeited
+    pub board: Board,
+    // Columns played from the start position, after any take-backs
+    pub moves: Vec<usize>,
+    pub result: GameResult,
+    pub forfeit: Option<Forfeit>,
+}
+
+pub struct Tournament {
+    yellow_player: Box<dyn Agent>,
+    blue_player: Box<dyn Agent>,
+    // Agents are seeded from this before the game, so it can be replayed
+    seed: Option<u64>,
+    // Position every game starts from
+    start: Board,
+    // Take-backs allowed per game, none unless a human is playing
+    take_backs: u32,
+}
+
+impl Tournament {
+    pub fn new(yellow_player: Box<dyn Agent>, blue_player: Box<dyn Agent>) -> Self {
+        Self {
+            yellow_player,
+            blue_player,
+            seed: None,
+            start: Board::default(),
+            take_backs: 0,
+        }
+    }
+
+    pub fn with_take_backs(mut self, take_backs: u32) -> Self {
+        self.take_backs = take_backs;
+        self
+    }
+
+    // Agents must be built for this position too
+    pub fn with_start(mut self, start: Board) -> Self {
+        self.start = start;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    // Consumes the tournament, as the agents keep state from the game they followed. An agent that fails to
+    // return a legal move loses the game rather than taking the process down
+    pub fn play(mut self) -> GameOutcome {
+        if let Some(seed) = self.seed {
+            // Mixed so that neighbouring seeds still give unrelated games
+            let game_seed = game_seed(seed, 0);
+            self.yellow_player.seed(game_seed);
+            self.blue_player.seed(game_seed.rotate_left(32));
+        }
+        let mut history = History::new(self.start);
+        let mut take_backs = self.take_backs;
+
+        loop {
+            let board = history.board();
+            let mover = board.active_player;
+            let decision = if mover == Player::Yellow {
+                self.yellow_player.decide(board)
+            } else {
+                self.blue_player.decide(board)
+            };
+
+            let played = decision.map_err(ForfeitReason::NoMove).and_then(|decision| match decision {
+                Decision::Move(m) => board.try_play_move(m).map(|_| Some(m)).map_err(ForfeitReason::IllegalMove),
+                Decision::TakeBack if take_backs > 0 && history.ply() >= 2 => Ok(None),
+                Decision::TakeBack => Err(ForfeitReason::TakeBack),
+            });
+            let selected_move = match played {
+                Ok(Some(m)) => m,
+                // Back to the same player's previous turn
+                Ok(None) => {
+                    take_backs -= 1;
+                    history.jump(history.ply() - 2);
+                    self.yellow_player.taken_back(history.board());
+                    self.blue_player.taken_back(history.board());
+                    continue;
+                }
+                Err(reason) => {
+                    return GameOutcome {
+                        board,
+                        moves: history.moves().to_vec(),
+                        result: GameResult::Win(mover.invert()),
+                        forfeit: Some(Forfeit { player: mover, reason }),
+                    }
+                }
+            };
+            let board = history.play(selected_move).expect("move was checked as legal");
+            self.yellow_player.record_move(selected_move, board);
+            self.blue_player.record_move(selected_move, board);
+
+            if let Some(result) = board.result() {
+                return GameOutcome { board, moves: history.moves().to_vec(), result, forfeit: None };
+            }
+        }
+    }
+}
+
+// SplitMix64 over the master seed and game number, so neighbouring games get unrelated seeds
+pub fn game_seed(master_seed: u64, game: u64) -> u64 {
+    let mut z = master_seed.wrapping_add(game.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::agent::{monty::Monty, randy::Randy};
+
+    use super::*;
+
+    fn replay(seed: u64) -> Board {
+        let board = Board::default();
+        Tournament::new(Box::new(Monty::new(board, 5, 2)), Box::new(Randy::new()))
+            .with_seed(seed)
+            .play()
+            .board
+    }
+
+    // Always plays off the edge of the board
+    struct Cheat;
+
+    impl Agent for Cheat {
+        fn select_move(&mut self, _board: Board) -> Result<usize, SearchError> {
+            Ok(9)
+        }
+
+        fn record_move(&mut self, _index: usize, board: Board) -> Board {
+            board
+        }
+    }
+
+    #[test]
+    pub fn illegal_move_forfeits_the_game() {
+        // Arrange
+        let tournament = Tournament::new(Box::new(Randy::new()), Box::new(Cheat));
+
+        // Act
+        let outcome = tournament.play();
+
+        // Assert
+        assert_eq!(outcome.result, GameResult::Win(Player::Yellow));
+        assert_eq!(
+            outcome.forfeit,
+            Some(Forfeit { player: Player::Blue, reason: ForfeitReason::IllegalMove(MoveError::OutOfRange(9)) })
+        );
+        assert_eq!(outcome.board.turn, 1);
+    }
+
+    // Plays the first legal column, asking once for a take-back on the given turn
+    struct Regretful {
+        take_back_at: Option<u32>,
+        taken_back: Rc<Cell<Option<Board>>>,
+    }
+
+    impl Regretful {
+        fn new(take_back_at: Option<u32>) -> (Box<Self>, Rc<Cell<Option<Board>>>) {
+            let taken_back = Rc::new(Cell::new(None));
+            (Box::new(Self { take_back_at, taken_back: taken_back.clone() }), taken_back)
+        }
+    }
+
+    impl Agent for Regretful {
+        fn select_move(&mut self, board: Board) -> Result<usize, SearchError> {
+            Ok(board.get_moves()[0])
+        }
+
+        fn record_move(&mut self, _index: usize, board: Board) -> Board {
+            board
+        }
+
+        fn decide(&mut self, board: Board) -> Result<Decision, SearchError> {
+            match self.take_back_at == Some(board.turn) && self.taken_back.get().is_none() {
+                true => Ok(Decision::TakeBack),
+                false => self.select_move(board).map(Decision::Move),
+            }
+        }
+
+        fn taken_back(&mut self, board: Board) {
+            self.taken_back.set(Some(board));
+        }
+    }
+
+    #[test]
+    pub fn take_back_rewinds_to_the_players_previous_turn() {
+        // Arrange
+        let (yellow, yellow_told) = Regretful::new(Some(4));
+        let (blue, blue_told) = Regretful::new(None);
+        let (plain_yellow, _) = Regretful::new(None);
+        let (plain_blue, _) = Regretful::new(None);
+
+        // Act
+        let outcome = Tournament::new(yellow, blue).with_take_backs(1).play();
+        let plain = Tournament::new(plain_yellow, plain_blue).play();
+
+        // Assert
+        assert_eq!(yellow_told.get(), Some(Board::from_moves("11").unwrap()));
+        assert_eq!(blue_told.get(), Some(Board::from_moves("11").unwrap()));
+        assert_eq!(outcome.moves, plain.moves);
+        assert_eq!(outcome.forfeit, None);
+    }
+
+    // Asks for a take-back on every turn from the given one on
+    struct Indecisive {
+        from_turn: u32,
+    }
+
+    impl Agent for Indecisive {
+        fn select_move(&mut self, board: Board) -> Result<usize, SearchError> {
+            Ok(board.get_moves()[0])
+        }
+
+        fn record_move(&mut self, _index: usize, board: Board) -> Board {
+            board
+        }
+
+        fn decide(&mut self, board: Board) -> Result<Decision, SearchError> {
+            match board.turn >= self.from_turn {
+                true => Ok(Decision::TakeBack),
+                false => self.select_move(board).map(Decision::Move),
+            }
+        }
+    }
+
+    #[test]
+    pub fn take_backs_that_cannot_be_honoured_forfeit() {
+        // Arrange
+        let (regretful, _) = Regretful::new(Some(4));
+        let always = || Box::new(Indecisive { from_turn: 0 });
+        let take_back_forfeit = |player| Some(Forfeit { player, reason: ForfeitReason::TakeBack });
+
+        // Act
+        let not_allowed = Tournament::new(regretful, Box::new(Randy::new())).play();
+        let always_asking = Tournament::new(always(), Box::new(Randy::new())).play();
+        let before_moving = Tournament::new(always(), Box::new(Randy::new())).with_take_backs(5).play();
+        let capped = Tournament::new(Box::new(Indecisive { from_turn: 2 }), Box::new(Randy::new())).with_take_backs(3).play();
+
+        // Assert
+        assert_eq!(not_allowed.forfeit, take_back_forfeit(Player::Yellow));
+        assert_eq!(not_allowed.board.turn, 4);
+        assert_eq!(always_asking.forfeit, take_back_forfeit(Player::Yellow));
+        assert_eq!(always_asking.result, GameResult::Win(Player::Blue));
+        assert_eq!(before_moving.forfeit, take_back_forfeit(Player::Yellow));
+        assert_eq!(before_moving.board.turn, 0);
+        assert_eq!(capped.forfeit, take_back_forfeit(Player::Yellow));
+        assert_eq!(capped.board.turn, 2);
+    }
+
+    #[test]
+    pub fn games_start_from_the_given_position() {
+        // Arrange
+        let start = Board::from_moves("4444").unwrap();
+        let tournament = Tournament::new(Box::new(Randy::new()), Box::new(Randy::new())).with_start(start);
+
+        // Act
+        let outcome = tournament.play();
+
+        // Assert
+        assert_eq!(outcome.board.turn as usize, 4 + outcome.moves.len());
+        assert_eq!(History::from_moves(start, &outcome.moves).map(|h| h.board()), Some(outcome.board));
+    }
+
+    #[test]
+    pub fn seeded_games_replay_exactly() {
+        // Act
+        let first = replay(3);
+        let second = replay(3);
+
+        // Assert
+        assert_eq!(first.yellow_bb, second.yellow_bb);
+        assert_eq!(first.blue_bb, second.blue_bb);
+    }
+
+    #[test]
+    pub fn game_seeds_differ_between_games_and_masters() {
+        assert_ne!(game_seed(1, 0), game_seed(1, 1));
+        assert_ne!(game_seed(1, 0), game_seed(2, 0));
+        assert_eq!(game_seed(5, 9), game_seed(5, 9));
+    }
+}
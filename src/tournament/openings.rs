@@ -0,0 +1,122 @@
+use std::{fs, path::Path};
+
+use crate::game::board::Board;
+
+use super::error::OpeningError;
+
+const BALANCED: &str = include_str!("../../openings/balanced.txt");
+
+// Positions games start from, taken in turn so that each pair of colour-swapped games shares one
+#[derive(Clone, Debug)]
+pub struct OpeningSuite {
+    openings: Vec<Board>,
+}
+
+impl Default for OpeningSuite {
+    fn default() -> Self {
+        Self::start_position()
+    }
+}
+
+impl OpeningSuite {
+    // Every game from the empty board
+    pub fn start_position() -> Self {
+        Self { openings: vec![Board::default()] }
+    }
+
+    // The short openings shipped in openings/balanced.txt
+    pub fn balanced() -> Self {
+        Self::parse(BALANCED).expect("the shipped opening book is valid")
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, OpeningError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    // One opening per line, either moves as 1-indexed columns ("4453") or a position string
+    // ("7/7/7/7/3b3/2byy2 y"). Blank lines and lines starting with '#' are skipped
+    pub fn parse(text: &str) -> Result<Self, OpeningError> {
+        let mut openings = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let board = match line.contains('/') {
+                true => Board::from_position(line),
+                false => Board::from_moves(line),
+            }
+            .map_err(|error| OpeningError::Invalid { line: i + 1, error })?;
+            if board.winner.is_some() {
+                return Err(OpeningError::Finished { line: i + 1 });
+            }
+            openings.push(board);
+        }
+        match openings.is_empty() {
+            true => Err(OpeningError::Empty),
+            false => Ok(Self { openings }),
+        }
+    }
+
+    pub fn openings(&self) -> &[Board] {
+        &self.openings
+    }
+
+    pub fn len(&self) -> usize {
+        self.openings.len()
+    }
+
+    // Never true, a suite always holds at least one opening
+    pub fn is_empty(&self) -> bool {
+        self.openings.is_empty()
+    }
+
+    // Cycles through the suite
+    pub fn get(&self, index: usize) -> Board {
+        self.openings[index % self.openings.len()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::error::{MoveError, PositionError};
+
+    use super::*;
+
+    #[test]
+    pub fn parses_moves_and_positions() {
+        // Arrange
+        let text = "# comment\n\n4453\n7/7/7/7/3b3/2byy2 y\n";
+
+        // Act
+        let suite = OpeningSuite::parse(text).unwrap();
+
+        // Assert
+        assert_eq!(suite.len(), 2);
+        assert_eq!(suite.get(0), Board::from_moves("4453").unwrap());
+        assert_eq!(suite.get(1), suite.get(0));
+        assert_eq!(suite.get(2), suite.get(0));
+    }
+
+    #[test]
+    pub fn reports_the_bad_line() {
+        assert!(matches!(
+            OpeningSuite::parse("44\n48\n"),
+            Err(OpeningError::Invalid { line: 2, error: PositionError::IllegalMove { ply: 2, error: MoveError::OutOfRange(7) } })
+        ));
+        assert!(matches!(OpeningSuite::parse("1212121"), Err(OpeningError::Finished { line: 1 })));
+        assert!(matches!(OpeningSuite::parse("# nothing\n"), Err(OpeningError::Empty)));
+        assert!(matches!(OpeningSuite::load("no/such/book.txt"), Err(OpeningError::Io(_))));
+    }
+
+    #[test]
+    pub fn balanced_suite_avoids_the_centre_opening() {
+        // Act
+        let suite = OpeningSuite::balanced();
+
+        // Assert
+        assert_eq!(suite.len(), 14);
+        assert!(suite.openings().iter().all(|b| b.turn == 2 && b.column_pieces[3] <= 1));
+        assert!(suite.openings().iter().all(|b| b.yellow_bb & Board::cell(3, 0) == 0));
+    }
+}
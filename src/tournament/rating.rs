@@ -0,0 +1,414 @@
+use std::{f64::consts::LN_10, fmt};
+
+use super::{error::RatingError, round_robin::RoundRobinResults};
+
+// Z score for the 95% error bars
+pub const CONFIDENCE_Z: f64 = 1.96;
+
+// Elo points per unit of Bradley-Terry log-strength
+const ELO_SCALE: f64 = 400.0 / LN_10;
+
+// The entrant whose rating is fixed, so ratings from different runs line up
+#[derive(Clone, Debug)]
+pub struct Anchor {
+    pub name: String,
+    pub elo: f64,
+}
+
+impl Default for Anchor {
+    fn default() -> Self {
+        Self { name: "randy".to_string(), elo: 0.0 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rating {
+    pub name: String,
+    pub elo: f64,
+    // Half width of the 95% interval relative to the anchor, infinite when the games say nothing about it
+    pub error: f64,
+    pub games: u32,
+    pub points: f64,
+}
+
+// Bradley-Terry ratings on the Elo scale, with draws counted as half a win for each side
+#[derive(Clone, Debug)]
+pub struct Ratings {
+    pub ratings: Vec<Rating>,
+    // Covariance of the log-strengths, zero for the anchor
+    covariance: Vec<Vec<f64>>,
+}
+
+impl Ratings {
+    // Each pair that met is credited with `prior_draws` extra drawn games, which keeps an unbeaten or winless
+    // record from pushing a rating to infinity, so it must be positive
+    pub fn fit(results: &RoundRobinResults, anchor: &Anchor, prior_draws: f64) -> Result<Self, RatingError> {
+        if !prior_draws.is_finite() || prior_draws <= 0.0 {
+            return Err(RatingError::InvalidPrior(prior_draws));
+        }
+        let anchor_index = results
+            .names
+            .iter()
+            .position(|n| *n == anchor.name)
+            .ok_or_else(|| RatingError::MissingAnchor(anchor.name.clone()))?;
+        let n = results.names.len();
+
+        let mut played = vec![vec![0.0; n]; n];
+        let mut scored = vec![vec![0.0; n]; n];
+        for game in results.games.iter() {
+            let (yellow, blue) = (game.pairing.yellow, game.pairing.blue);
+            played[yellow][blue] += 1.0;
+            played[blue][yellow] += 1.0;
+            match game.winner() {
+                Some(winner) => scored[winner][if winner == yellow { blue } else { yellow }] += 1.0,
+                None => {
+                    scored[yellow][blue] += 0.5;
+                    scored[blue][yellow] += 0.5;
+                }
+            }
+        }
+        for i in 0..n {
+            for j in 0..n {
+                if played[i][j] > 0.0 {
+                    played[i][j] += prior_draws;
+                    scored[i][j] += prior_draws / 2.0;
+                }
+            }
+        }
+
+        let strengths = minorize_maximize(&played, &scored);
+        let theta: Vec<f64> = strengths.iter().map(|s| s.ln() - strengths[anchor_index].ln()).collect();
+        let covariance = covariance(&played, &theta, anchor_index);
+
+        let ratings = (0..n)
+            .map(|i| {
+                let total = results.total(i);
+                Rating {
+                    name: results.names[i].clone(),
+                    elo: anchor.elo + ELO_SCALE * theta[i],
+                    error: CONFIDENCE_Z * ELO_SCALE * covariance[i][i].sqrt(),
+                    games: total.games(),
+                    points: total.points(),
+                }
+            })
+            .collect();
+        Ok(Self { ratings, covariance })
+    }
+
+    // Probability that a is really stronger than b, given the ratings and how uncertain they are
+    pub fn likelihood_of_superiority(&self, a: usize, b: usize) -> f64 {
+        let difference = (self.ratings[a].elo - self.ratings[b].elo) / ELO_SCALE;
+        let variance = self.covariance[a][a] + self.covariance[b][b] - 2.0 * self.covariance[a][b];
+        match variance.is_finite() && variance > 0.0 {
+            true => normal_cdf(difference / variance.sqrt()),
+            false => 0.5,
+        }
+    }
+
+    // Entrant indices by rating, best first
+    pub fn order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.ratings.len()).collect();
+        order.sort_by(|a, b| self.ratings[*b].elo.total_cmp(&self.ratings[*a].elo));
+        order
+    }
+}
+
+// Hunter's MM iteration for Bradley-Terry strengths, normalised to a geometric mean of one
+fn minorize_maximize(played: &[Vec<f64>], scored: &[Vec<f64>]) -> Vec<f64> {
+    let n = played.len();
+    let mut strengths = vec![1.0; n];
+    for _ in 0..10_000 {
+        let mut next = strengths.clone();
+        for i in 0..n {
+            let points: f64 = scored[i].iter().sum();
+            let expected: f64 = (0..n)
+                .filter(|j| played[i][*j] > 0.0)
+                .map(|j| played[i][j] / (strengths[i] + strengths[j]))
+                .sum();
+            if expected > 0.0 {
+                next[i] = points / expected;
+            }
+        }
+        let log_mean = next.iter().map(|s| s.ln()).sum::<f64>() / n as f64;
+        next.iter_mut().for_each(|s| *s /= log_mean.exp());
+        let change = next.iter().zip(strengths.iter()).map(|(a, b)| (a.ln() - b.ln()).abs()).fold(0.0, f64::max);
+        strengths = next;
+        if change < 1e-10 {
+            break;
+        }
+    }
+    strengths
+}
+
+// Inverse of the Fisher information with the anchor held fixed. Only entrants linked to the anchor through
+// games are inverted, any others get infinite variance
+fn covariance(played: &[Vec<f64>], theta: &[f64], anchor: usize) -> Vec<Vec<f64>> {
+    let n = played.len();
+    let linked = linked_to(played, anchor);
+    let free: Vec<usize> = (0..n).filter(|i| *i != anchor && linked[*i]).collect();
+    let mut information = vec![vec![0.0; free.len()]; free.len()];
+    for (a, &i) in free.iter().enumerate() {
+        for j in 0..n {
+            if i == j || played[i][j] == 0.0 {
+                continue;
+            }
+            let p = 1.0 / (1.0 + (theta[j] - theta[i]).exp());
+            let weight = played[i][j] * p * (1.0 - p);
+            information[a][a] += weight;
+            if let Some(b) = free.iter().position(|k| *k == j) {
+                information[a][b] -= weight;
+            }
+        }
+    }
+
+    let mut covariance = vec![vec![0.0; n]; n];
+    (0..n).filter(|i| !linked[*i]).for_each(|i| covariance[i][i] = f64::INFINITY);
+    match invert(information) {
+        Some(inverse) => {
+            for (a, &i) in free.iter().enumerate() {
+                for (b, &j) in free.iter().enumerate() {
+                    covariance[i][j] = inverse[a][b];
+                }
+            }
+        }
+        None => free.iter().for_each(|i| covariance[*i][*i] = f64::INFINITY),
+    }
+    covariance
+}
+
+// Entrants reachable from `start` through pairs that have played each other
+fn linked_to(played: &[Vec<f64>], start: usize) -> Vec<bool> {
+    let mut linked = vec![false; played.len()];
+    linked[start] = true;
+    let mut frontier = vec![start];
+    while let Some(i) = frontier.pop() {
+        for j in 0..played.len() {
+            if played[i][j] > 0.0 && !linked[j] {
+                linked[j] = true;
+                frontier.push(j);
+            }
+        }
+    }
+    linked
+}
+
+// Gauss-Jordan elimination with partial pivoting, none for a singular matrix
+fn invert(mut matrix: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| matrix[*a][column].abs().total_cmp(&matrix[*b][column].abs()))?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+        let scale = matrix[column][column];
+        for k in 0..n {
+            matrix[column][k] /= scale;
+            inverse[column][k] /= scale;
+        }
+        for row in 0..n {
+            if row == column {
+                continue;
+            }
+            let factor = matrix[row][column];
+            for k in 0..n {
+                matrix[row][k] -= factor * matrix[column][k];
+                inverse[row][k] -= factor * inverse[column][k];
+            }
+        }
+    }
+    Some(inverse)
+}
+
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+// Abramowitz and Stegun 7.1.26, accurate to about 1.5e-7
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let polynomial = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let y = 1.0 - polynomial * (-x * x).exp();
+    y.copysign(x)
+}
+
+impl fmt::Display for Ratings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.ratings.iter().map(|r| r.name.len()).max().unwrap_or(0).max(4);
+        writeln!(f, "{:width$} {:>7} {:>7} {:>6} {:>7} {:>6}", "name", "elo", "+/-", "games", "points", "los")?;
+        let order = self.order();
+        for (rank, &i) in order.iter().enumerate() {
+            let rating = &self.ratings[i];
+            // Against the entrant ranked just below
+            let los = match order.get(rank + 1) {
+                Some(&next) => format!("{:.1}%", 100.0 * self.likelihood_of_superiority(i, next)),
+                None => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{:width$} {:>7.1} {:>7.1} {:>6} {:>7.1} {los:>6}",
+                rating.name, rating.elo, rating.error, rating.games, rating.points
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        game::{board::Board, player::Player, result::GameResult},
+        tournament::{
+            round_robin::{GameRecord, Pairing},
+            GameOutcome,
+        },
+    };
+
+    use super::*;
+
+    fn game(yellow: usize, blue: usize, result: GameResult) -> GameRecord {
+        GameRecord { pairing: Pairing { yellow, blue, opening: 0 }, outcome: GameOutcome { board: Board::default(), moves: vec![], result, forfeit: None } }
+    }
+
+    fn results(names: &[&str], games: Vec<GameRecord>) -> RoundRobinResults {
+        RoundRobinResults::new(names.iter().map(|n| n.to_string()).collect(), games)
+    }
+
+    #[test]
+    pub fn even_results_give_even_ratings() {
+        // Arrange
+        let games = vec![
+            game(0, 1, GameResult::Win(Player::Yellow)),
+            game(1, 0, GameResult::Win(Player::Yellow)),
+            game(0, 1, GameResult::Draw),
+        ];
+
+        // Act
+        let ratings = Ratings::fit(&results(&["randy", "monty"], games), &Anchor::default(), 1.0).unwrap();
+
+        // Assert
+        assert_eq!(ratings.ratings[0].elo, 0.0);
+        assert!(ratings.ratings[1].elo.abs() < 1e-6);
+        assert!((ratings.likelihood_of_superiority(1, 0) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    pub fn winning_record_rates_above_the_anchor() {
+        // Arrange
+        let mut games = vec![];
+        for i in 0..10 {
+            let winner = if i < 8 { 1 } else { 0 };
+            let (yellow, blue) = if i % 2 == 0 { (0, 1) } else { (1, 0) };
+            let colour = if winner == yellow { Player::Yellow } else { Player::Blue };
+            games.push(game(yellow, blue, GameResult::Win(colour)));
+        }
+        let anchor = Anchor { name: "randy".to_string(), elo: 1000.0 };
+
+        // Act
+        let ratings = Ratings::fit(&results(&["randy", "monty"], games), &anchor, 1.0).unwrap();
+
+        // Assert
+        // 8.5 points to 2.5 over 11 games including the virtual draw
+        let expected = 1000.0 + 400.0 * f64::log10(8.5 / 2.5);
+        assert_eq!(ratings.ratings[0].elo, 1000.0);
+        assert_eq!(ratings.ratings[0].error, 0.0);
+        assert!((ratings.ratings[1].elo - expected).abs() < 1e-3);
+        assert!(ratings.ratings[1].error > 100.0 && ratings.ratings[1].error < 1000.0);
+        assert!(ratings.likelihood_of_superiority(1, 0) > 0.95);
+        assert_eq!(ratings.order(), vec![1, 0]);
+        assert_eq!(ratings.ratings[1].points, 8.0);
+    }
+
+    #[test]
+    pub fn ratings_chain_through_common_opponents() {
+        // Arrange
+        // a beats randy and b loses to randy, but a and b never meet
+        let games = vec![
+            game(1, 0, GameResult::Win(Player::Yellow)),
+            game(0, 1, GameResult::Win(Player::Blue)),
+            game(2, 0, GameResult::Win(Player::Blue)),
+            game(0, 2, GameResult::Win(Player::Yellow)),
+        ];
+
+        // Act
+        let ratings = Ratings::fit(&results(&["randy", "a", "b"], games), &Anchor::default(), 1.0).unwrap();
+
+        // Assert
+        assert!(ratings.ratings[1].elo > 0.0);
+        assert!(ratings.ratings[2].elo < 0.0);
+        assert!(ratings.likelihood_of_superiority(1, 2) > ratings.likelihood_of_superiority(1, 0));
+    }
+
+    #[test]
+    pub fn unconnected_entrants_have_unbounded_error() {
+        // Act
+        let ratings = Ratings::fit(&results(&["randy", "loner"], vec![]), &Anchor::default(), 1.0).unwrap();
+
+        // Assert
+        assert!(ratings.ratings[1].error.is_infinite());
+        assert_eq!(ratings.likelihood_of_superiority(1, 0), 0.5);
+    }
+
+    #[test]
+    pub fn unconnected_entrant_leaves_the_others_bounded() {
+        // Arrange
+        let games = vec![
+            game(0, 1, GameResult::Win(Player::Yellow)),
+            game(1, 0, GameResult::Win(Player::Yellow)),
+            game(0, 1, GameResult::Win(Player::Blue)),
+        ];
+
+        // Act
+        let ratings = Ratings::fit(&results(&["randy", "monty", "loner"], games), &Anchor::default(), 1.0).unwrap();
+
+        // Assert
+        assert_eq!(ratings.ratings[0].error, 0.0);
+        assert!(ratings.ratings[1].error.is_finite() && ratings.ratings[1].error > 0.0);
+        assert!(ratings.ratings[2].error.is_infinite());
+        assert!(ratings.likelihood_of_superiority(1, 0) > 0.5 && ratings.likelihood_of_superiority(1, 0) < 1.0);
+        assert_eq!(ratings.likelihood_of_superiority(2, 0), 0.5);
+    }
+
+    #[test]
+    pub fn winless_entrant_needs_a_prior() {
+        // Arrange
+        let games = vec![
+            game(0, 1, GameResult::Win(Player::Yellow)),
+            game(1, 0, GameResult::Win(Player::Blue)),
+            game(1, 2, GameResult::Win(Player::Yellow)),
+            game(2, 1, GameResult::Win(Player::Blue)),
+            game(0, 2, GameResult::Win(Player::Yellow)),
+            game(2, 0, GameResult::Win(Player::Blue)),
+        ];
+        let results = results(&["randy", "monty", "loser"], games);
+
+        // Act
+        let without_prior = Ratings::fit(&results, &Anchor::default(), 0.0);
+        let ratings = Ratings::fit(&results, &Anchor::default(), 1.0).unwrap();
+
+        // Assert
+        assert_eq!(without_prior.err(), Some(RatingError::InvalidPrior(0.0)));
+        assert!(ratings.ratings.iter().all(|r| r.elo.is_finite() && r.error.is_finite()));
+        assert_eq!(ratings.order(), vec![0, 1, 2]);
+        assert!(ratings.ratings[2].elo < 0.0);
+        assert_eq!(ratings.ratings[2].points, 0.0);
+    }
+
+    #[test]
+    pub fn missing_anchor_cannot_be_fitted() {
+        assert_eq!(
+            Ratings::fit(&results(&["monty"], vec![]), &Anchor::default(), 1.0).err(),
+            Some(RatingError::MissingAnchor("randy".to_string()))
+        );
+        assert!(Ratings::fit(&results(&["randy"], vec![]), &Anchor::default(), f64::NAN).is_err());
+    }
+
+    #[test]
+    pub fn normal_cdf_matches_known_values() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-4);
+        assert!((normal_cdf(-1.0) - 0.158_655).abs() < 1e-5);
+    }
+}
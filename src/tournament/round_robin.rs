@@ -0,0 +1,395 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{
+    agent::Agent,
+    game::{board::Board, player::Player, result::GameResult},
+};
+
+use super::{game_seed, openings::OpeningSuite, GameOutcome, Tournament};
+
+// Builds a fresh agent for each game, given the position the game starts from. Agents aren't Send, so each
+// worker thread builds its own
+pub type AgentFactory = Box<dyn Fn(Board) -> Box<dyn Agent> + Send + Sync>;
+
+pub struct Entrant {
+    pub name: String,
+    factory: AgentFactory,
+}
+
+impl Entrant {
+    pub fn new(name: &str, factory: AgentFactory) -> Self {
+        Self { name: name.to_string(), factory }
+    }
+
+    pub fn build(&self, board: Board) -> Box<dyn Agent> {
+        (self.factory)(board)
+    }
+}
+
+// Indices into the entrant list and the opening suite
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pairing {
+    pub yellow: usize,
+    pub blue: usize,
+    pub opening: usize,
+}
+
+// Reported as each game finishes, in whatever order the workers finish them
+#[derive(Clone, Debug)]
+pub struct Progress {
+    pub completed: usize,
+    pub total: usize,
+    // Position of the game in the schedule
+    pub number: usize,
+    pub game: GameRecord,
+}
+
+// Every entrant plays every other `rounds` times with each colour
+pub struct RoundRobin {
+    entrants: Vec<Entrant>,
+    rounds: usize,
+    seed: Option<u64>,
+    threads: usize,
+    openings: OpeningSuite,
+}
+
+impl RoundRobin {
+    pub fn new(rounds: usize) -> Self {
+        Self { entrants: vec![], rounds, seed: None, threads: 1, openings: OpeningSuite::default() }
+    }
+
+    pub fn with_openings(mut self, openings: OpeningSuite) -> Self {
+        self.openings = openings;
+        self
+    }
+
+    // Games are shared out between this many workers. Seeded results don't depend on it
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn with_entrant(mut self, entrant: Entrant) -> Self {
+        self.entrants.push(entrant);
+        self
+    }
+
+    // Game n of the schedule is seeded from game_seed(seed, n), so a single game can be replayed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn entrants(&self) -> &[Entrant] {
+        &self.entrants
+    }
+
+    // Each round plays every pairing once, with the colours swapped in the game that follows it. Both games
+    // of a pair start from the same opening, and each pair takes the next opening in the suite
+    pub fn schedule(&self) -> Vec<Pairing> {
+        let n = self.entrants.len();
+        let mut pairings = vec![];
+        for _ in 0..self.rounds {
+            for first in 0..n {
+                for second in first + 1..n {
+                    let opening = pairings.len() / 2 % self.openings.len();
+                    pairings.push(Pairing { yellow: first, blue: second, opening });
+                    pairings.push(Pairing { yellow: second, blue: first, opening });
+                }
+            }
+        }
+        pairings
+    }
+
+    pub fn play_game(&self, number: usize, pairing: Pairing) -> GameRecord {
+        let board = self.openings.get(pairing.opening);
+        let mut tournament =
+            Tournament::new(self.entrants[pairing.yellow].build(board), self.entrants[pairing.blue].build(board))
+                .with_start(board);
+        if let Some(seed) = self.seed {
+            tournament = tournament.with_seed(game_seed(seed, number as u64));
+        }
+        GameRecord { pairing, outcome: tournament.play() }
+    }
+
+    pub fn run(&self) -> RoundRobinResults {
+        self.run_with_progress(|_| {})
+    }
+
+    // Workers take the next unplayed game from the schedule until it runs out. Results keep schedule order
+    pub fn run_with_progress(&self, progress: impl Fn(&Progress) + Sync) -> RoundRobinResults {
+        let schedule = self.schedule();
+        let next = AtomicUsize::new(0);
+        let finished: Mutex<(usize, Vec<Option<GameRecord>>)> = Mutex::new((0, vec![None; schedule.len()]));
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(schedule.len()) {
+                scope.spawn(|| loop {
+                    let number = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(pairing) = schedule.get(number) else {
+                        break;
+                    };
+                    let game = self.play_game(number, *pairing);
+                    let completed = {
+                        let mut finished = finished.lock().unwrap();
+                        finished.1[number] = Some(game.clone());
+                        finished.0 += 1;
+                        finished.0
+                    };
+                    progress(&Progress { completed, total: schedule.len(), number, game });
+                });
+            }
+        });
+
+        let games = finished.into_inner().unwrap().1.into_iter().map(|g| g.expect("every game is played")).collect();
+        RoundRobinResults::new(self.entrants.iter().map(|e| e.name.clone()).collect(), games)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub pairing: Pairing,
+    pub outcome: GameOutcome,
+}
+
+impl GameRecord {
+    // Index of the winning entrant, none for a draw
+    pub fn winner(&self) -> Option<usize> {
+        match self.outcome.result {
+            GameResult::Win(Player::Yellow) => Some(self.pairing.yellow),
+            GameResult::Win(Player::Blue) => Some(self.pairing.blue),
+            _ => None,
+        }
+    }
+
+    pub fn length(&self) -> u32 {
+        self.outcome.board.turn
+    }
+}
+
+// Results of one entrant against another, or against the whole field
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Score {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    // Losses by forfeit, also counted in losses
+    pub forfeits: u32,
+    pub total_length: u64,
+}
+
+impl Score {
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.draws
+    }
+
+    pub fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+
+    pub fn average_length(&self) -> Option<f64> {
+        match self.games() {
+            0 => None,
+            games => Some(self.total_length as f64 / games as f64),
+        }
+    }
+
+    fn add(&mut self, record: &GameRecord, entrant: usize) {
+        match record.winner() {
+            Some(winner) if winner == entrant => self.wins += 1,
+            Some(_) => {
+                self.losses += 1;
+                if record.outcome.forfeit.is_some() {
+                    self.forfeits += 1;
+                }
+            }
+            None => self.draws += 1,
+        }
+        self.total_length += record.length() as u64;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RoundRobinResults {
+    pub names: Vec<String>,
+    pub games: Vec<GameRecord>,
+    // cross_table[a][b] is a's score against b
+    cross_table: Vec<Vec<Score>>,
+}
+
+impl RoundRobinResults {
+    pub fn new(names: Vec<String>, games: Vec<GameRecord>) -> Self {
+        let mut cross_table = vec![vec![Score::default(); names.len()]; names.len()];
+        for game in games.iter() {
+            let Pairing { yellow, blue, .. } = game.pairing;
+            cross_table[yellow][blue].add(game, yellow);
+            cross_table[blue][yellow].add(game, blue);
+        }
+        Self { names, games, cross_table }
+    }
+
+    pub fn head_to_head(&self, entrant: usize, opponent: usize) -> Score {
+        self.cross_table[entrant][opponent]
+    }
+
+    pub fn total(&self, entrant: usize) -> Score {
+        self.cross_table[entrant].iter().fold(Score::default(), |total, s| Score {
+            wins: total.wins + s.wins,
+            losses: total.losses + s.losses,
+            draws: total.draws + s.draws,
+            forfeits: total.forfeits + s.forfeits,
+            total_length: total.total_length + s.total_length,
+        })
+    }
+
+    // Entrant indices by points, best first
+    pub fn standings(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.names.len()).collect();
+        order.sort_by(|a, b| self.total(*b).points().total_cmp(&self.total(*a).points()));
+        order
+    }
+}
+
+impl fmt::Display for RoundRobinResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.names.iter().map(|n| n.len()).max().unwrap_or(0).max(6);
+        write!(f, "{:width$}", "w/l/d")?;
+        for name in self.names.iter() {
+            write!(f, " {name:>width$}")?;
+        }
+        writeln!(f, " {:>8} {:>7}", "points", "length")?;
+        for entrant in self.standings() {
+            write!(f, "{:width$}", self.names[entrant])?;
+            for opponent in 0..self.names.len() {
+                let score = self.head_to_head(entrant, opponent);
+                let cell = match entrant == opponent {
+                    true => "-".to_string(),
+                    false => format!("{}/{}/{}", score.wins, score.losses, score.draws),
+                };
+                write!(f, " {cell:>width$}")?;
+            }
+            let total = self.total(entrant);
+            let length = total.average_length().map_or("-".to_string(), |l| format!("{l:.1}"));
+            writeln!(f, " {:>8.1} {length:>7}", total.points())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        agent::{monty::Monty, randy::Randy, solver::Solver},
+        game::history::History,
+    };
+
+    use super::*;
+
+    fn randy() -> Entrant {
+        Entrant::new("randy", Box::new(|_| Box::new(Randy::new())))
+    }
+
+    #[test]
+    pub fn schedule_swaps_colours_for_every_pairing() {
+        // Arrange
+        let round_robin = RoundRobin::new(2).with_entrant(randy()).with_entrant(randy()).with_entrant(randy());
+
+        // Act
+        let schedule = round_robin.schedule();
+
+        // Assert
+        assert_eq!(schedule.len(), 2 * 3 * 2);
+        for first in 0..3 {
+            for second in 0..3 {
+                let games = schedule.iter().filter(|p| p.yellow == first && p.blue == second).count();
+                assert_eq!(games, if first == second { 0 } else { 2 });
+            }
+        }
+    }
+
+    #[test]
+    pub fn cross_table_is_consistent_from_both_sides() {
+        // Arrange
+        let round_robin = RoundRobin::new(3)
+            .with_seed(11)
+            .with_entrant(randy())
+            .with_entrant(Entrant::new("solver", Box::new(|_| Box::new(Solver::new(4, None)))));
+
+        // Act
+        let results = round_robin.run();
+
+        // Assert
+        let (randy, solver) = (results.head_to_head(0, 1), results.head_to_head(1, 0));
+        assert_eq!(randy.games(), 6);
+        assert_eq!(randy.wins, solver.losses);
+        assert_eq!(randy.draws, solver.draws);
+        assert_eq!(randy.total_length, solver.total_length);
+        assert_eq!(results.standings(), vec![1, 0]);
+        assert!(results.total(1).average_length().unwrap() >= 7.0);
+        assert_eq!(results.total(0).points() + results.total(1).points(), 6.0);
+    }
+
+    #[test]
+    pub fn colour_swapped_games_share_an_opening() {
+        // Arrange
+        let openings = OpeningSuite::parse("31\n52\n").unwrap();
+        let round_robin = RoundRobin::new(2).with_openings(openings.clone()).with_entrant(randy()).with_entrant(randy());
+
+        // Act
+        let results = round_robin.run();
+
+        // Assert
+        let used: Vec<usize> = results.games.iter().map(|g| g.pairing.opening).collect();
+        assert_eq!(used, vec![0, 0, 1, 1]);
+        for game in results.games.iter() {
+            let replayed = History::from_moves(openings.get(game.pairing.opening), &game.outcome.moves);
+            assert_eq!(replayed.map(|h| h.board()), Some(game.outcome.board));
+        }
+    }
+
+    #[test]
+    pub fn seeded_results_do_not_depend_on_thread_count() {
+        // Arrange
+        let round_robin = |threads| {
+            RoundRobin::new(2)
+                .with_seed(5)
+                .with_threads(threads)
+                .with_entrant(randy())
+                .with_entrant(Entrant::new("monty", Box::new(|board| Box::new(Monty::new(board, 5, 2)))))
+                .with_entrant(randy())
+        };
+        let reported = AtomicUsize::new(0);
+
+        // Act
+        let sequential = round_robin(1).run();
+        let parallel = round_robin(4).run_with_progress(|p| {
+            reported.fetch_add(1, Ordering::Relaxed);
+            assert!(p.completed <= p.total);
+        });
+
+        // Assert
+        assert_eq!(reported.into_inner(), 12);
+        assert_eq!(parallel.games.len(), sequential.games.len());
+        for (a, b) in sequential.games.iter().zip(parallel.games.iter()) {
+            assert_eq!(a.pairing, b.pairing);
+            assert_eq!(a.outcome.board, b.outcome.board);
+        }
+    }
+
+    #[test]
+    pub fn empty_results_have_no_average_length() {
+        // Act
+        let results = RoundRobinResults::new(vec!["a".to_string(), "b".to_string()], vec![]);
+
+        // Assert
+        assert_eq!(results.total(0).average_length(), None);
+        assert!(results.to_string().contains("a"));
+    }
+}
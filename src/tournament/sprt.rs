@@ -0,0 +1,246 @@
+use std::fmt;
+
+use crate::game::{player::Player, result::GameResult};
+
+use super::{game_seed, openings::OpeningSuite, round_robin::Entrant, Tournament};
+
+// Test of H0: candidate is elo0 stronger than the baseline, against H1: it is elo1 stronger
+#[derive(Clone, Copy, Debug)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    // False positive and false negative rates
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Default for Sprt {
+    fn default() -> Self {
+        Self { elo0: 0.0, elo1: 10.0, alpha: 0.05, beta: 0.05 }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SprtDecision {
+    // The candidate is stronger by at least elo1
+    AcceptH1,
+    // The candidate is no stronger than elo0
+    AcceptH0,
+}
+
+impl Sprt {
+    pub fn lower_bound(&self) -> f64 {
+        (self.beta / (1.0 - self.alpha)).ln()
+    }
+
+    pub fn upper_bound(&self) -> f64 {
+        ((1.0 - self.beta) / self.alpha).ln()
+    }
+
+    // Generalised SPRT on the score of each pair, normal approximation as used by fishtest
+    pub fn llr(&self, pentanomial: &[u32; 5]) -> f64 {
+        let pairs: u32 = pentanomial.iter().sum();
+        if pairs == 0 {
+            return 0.0;
+        }
+        let n = pairs as f64;
+        let score = |bin: usize| bin as f64 / 4.0;
+        let mean = (0..5).map(|b| pentanomial[b] as f64 * score(b)).sum::<f64>() / n;
+        let variance = (0..5).map(|b| pentanomial[b] as f64 * (score(b) - mean).powi(2)).sum::<f64>() / n;
+        // A run of identical pairs has no spread yet, which would make every result look decisive
+        let variance = variance.max(1e-3);
+        let (s0, s1) = (expected_score(self.elo0), expected_score(self.elo1));
+        n * (s1 - s0) * (2.0 * mean - s0 - s1) / (2.0 * variance)
+    }
+
+    pub fn decide(&self, llr: f64) -> Option<SprtDecision> {
+        if llr >= self.upper_bound() {
+            Some(SprtDecision::AcceptH1)
+        } else if llr <= self.lower_bound() {
+            Some(SprtDecision::AcceptH0)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SprtState {
+    // Pairs by the candidate's points over both games, in half points from 0 to 2
+    pub pentanomial: [u32; 5],
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub llr: f64,
+    pub decision: Option<SprtDecision>,
+}
+
+impl SprtState {
+    pub fn pairs(&self) -> u32 {
+        self.pentanomial.iter().sum()
+    }
+}
+
+impl fmt::Display for SprtState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pairs {} (+{} -{} ={}) pentanomial {:?} llr {:.3}",
+            self.pairs(),
+            self.wins,
+            self.losses,
+            self.draws,
+            self.pentanomial,
+            self.llr
+        )?;
+        if let Some(decision) = self.decision {
+            write!(f, " {decision:?}")?;
+        }
+        Ok(())
+    }
+}
+
+// Plays a candidate against a baseline in pairs of games, the same opening and seed with colours swapped,
+// until the test reaches a decision or the pair limit runs out
+pub struct SprtMatch {
+    baseline: Entrant,
+    candidate: Entrant,
+    sprt: Sprt,
+    max_pairs: u32,
+    seed: u64,
+    openings: OpeningSuite,
+}
+
+impl SprtMatch {
+    pub fn new(baseline: Entrant, candidate: Entrant, sprt: Sprt, max_pairs: u32, seed: u64) -> Self {
+        Self { baseline, candidate, sprt, max_pairs, seed, openings: OpeningSuite::default() }
+    }
+
+    // Pair n starts from the n-th opening, cycling through the suite
+    pub fn with_openings(mut self, openings: OpeningSuite) -> Self {
+        self.openings = openings;
+        self
+    }
+
+    // Candidate as yellow then as blue
+    pub fn play_pair(&self, pair: u32) -> [GameResult; 2] {
+        let seed = game_seed(self.seed, pair as u64);
+        let board = self.openings.get(pair as usize);
+        let first = Tournament::new(self.candidate.build(board), self.baseline.build(board))
+            .with_start(board)
+            .with_seed(seed)
+            .play();
+        let second = Tournament::new(self.baseline.build(board), self.candidate.build(board))
+            .with_start(board)
+            .with_seed(seed)
+            .play();
+        [first.result, second.result]
+    }
+
+    // Calls `progress` after every pair with the running state
+    pub fn run(&self, mut progress: impl FnMut(&SprtState)) -> SprtState {
+        let mut state = SprtState::default();
+        for pair in 0..self.max_pairs {
+            let mut half_points = 0;
+            for (game, result) in self.play_pair(pair).iter().enumerate() {
+                let candidate = if game == 0 { Player::Yellow } else { Player::Blue };
+                match result {
+                    GameResult::Win(winner) if *winner == candidate => {
+                        state.wins += 1;
+                        half_points += 2;
+                    }
+                    GameResult::Win(_) => state.losses += 1,
+                    GameResult::Draw => {
+                        state.draws += 1;
+                        half_points += 1;
+                    }
+                }
+            }
+            state.pentanomial[half_points] += 1;
+            state.llr = self.sprt.llr(&state.pentanomial);
+            state.decision = self.sprt.decide(state.llr);
+            progress(&state);
+            if state.decision.is_some() {
+                break;
+            }
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::agent::{randy::Randy, solver::Solver};
+
+    use super::*;
+
+    fn randy() -> Entrant {
+        Entrant::new("randy", Box::new(|_| Box::new(Randy::new())))
+    }
+
+    fn solver() -> Entrant {
+        Entrant::new("solver", Box::new(|_| Box::new(Solver::new(4, None))))
+    }
+
+    #[test]
+    pub fn bounds_follow_error_rates() {
+        // Arrange
+        let sprt = Sprt::default();
+
+        // Assert
+        assert!((sprt.upper_bound() - 2.944).abs() < 1e-3);
+        assert!((sprt.lower_bound() + 2.944).abs() < 1e-3);
+        assert_eq!(sprt.decide(3.0), Some(SprtDecision::AcceptH1));
+        assert_eq!(sprt.decide(-3.0), Some(SprtDecision::AcceptH0));
+        assert_eq!(sprt.decide(0.0), None);
+    }
+
+    #[test]
+    pub fn llr_leans_towards_the_hypothesis_the_results_fit() {
+        // Arrange
+        let sprt = Sprt { elo0: 0.0, elo1: 50.0, ..Sprt::default() };
+
+        // Act
+        let winning = sprt.llr(&[5, 10, 30, 30, 25]);
+        let even = sprt.llr(&[10, 20, 40, 20, 10]);
+
+        // Assert
+        assert!(winning > 0.0);
+        assert!(even < 0.0);
+        assert_eq!(sprt.llr(&[0; 5]), 0.0);
+    }
+
+    #[test]
+    pub fn stronger_candidate_is_accepted() {
+        // Arrange
+        let sprt = Sprt { elo0: 0.0, elo1: 100.0, ..Sprt::default() };
+        let sprt_match = SprtMatch::new(randy(), solver(), sprt, 50, 3);
+        let mut updates = 0;
+
+        // Act
+        let state = sprt_match.run(|_| updates += 1);
+
+        // Assert
+        assert_eq!(state.decision, Some(SprtDecision::AcceptH1));
+        assert_eq!(updates, state.pairs());
+        assert_eq!(state.wins + state.losses + state.draws, 2 * state.pairs());
+    }
+
+    #[test]
+    pub fn weaker_candidate_is_rejected() {
+        // Arrange
+        let sprt = Sprt { elo0: 0.0, elo1: 100.0, ..Sprt::default() };
+        let sprt_match = SprtMatch::new(solver(), randy(), sprt, 50, 3).with_openings(OpeningSuite::balanced());
+
+        // Act
+        let state = sprt_match.run(|_| {});
+
+        // Assert
+        assert_eq!(state.decision, Some(SprtDecision::AcceptH0));
+        assert!(state.llr <= sprt.lower_bound());
+    }
+}